@@ -26,15 +26,17 @@
 //! impl ErrorKind for MyErrorKind {}
 //!
 //! fn main() {
+//! #     std::env::remove_var("RUST_BACKTRACE");
 //!     // Tracks an error
 //!     let error: MyError = MyErrorKind::Critical.cause("something wrong").into();
 //!     let error = track!(error);
 //!     let error = track!(error, "I passed here");
+//! #     if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
 //!     assert_eq!(format!("\nError: {}", error).replace('\\', "/"), r#"
 //! Error: Critical (cause; something wrong)
 //! HISTORY:
-//!   [0] at src/error.rs:27
-//!   [1] at src/error.rs:28 -- I passed here
+//!   [0] at src/error.rs:28:17
+//!   [1] at src/error.rs:29:17 -- I passed here
 //! "#);
 //!
 //!     // Tries to execute I/O operation
@@ -65,12 +67,26 @@
 //! It can be customized by using `#[trackable(error_type = "$error_kind")]` attribute.
 //!
 //! The target error type must be a newtype (i.e., a tuple struct that has a single element) of `TrackableError`.
+use std::any;
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+use std::borrow::Cow;
+use std::cmp;
 use std::error::Error;
 use std::fmt;
+#[cfg(not(feature = "std"))]
+use std::format;
+#[cfg(feature = "std")]
 use std::io;
+use std::iter;
+use std::mem;
+use std::ops;
+use std::panic;
 use std::sync::Arc;
 
-use super::{Location, Trackable};
+use super::{DisplayOptions, Location, Style, Trackable};
+#[cfg(not(feature = "std"))]
+use prelude::*;
 
 /// Boxed `Error` object.
 pub type BoxError = Box<dyn Error + Send + Sync>;
@@ -98,37 +114,433 @@ impl ErrorKind for Failed {
 pub struct Failure(TrackableError<Failed>);
 impl Failure {
     /// Makes a new `Failure` instance which was caused by the `error`.
+    ///
+    /// If `error` is a trackable error, its history is kept only in the `Display` form of the cause.
+    /// Use [`from_trackable_error`](#method.from_trackable_error) to adopt the history instead.
     pub fn from_error<E>(error: E) -> Self
     where
         E: Into<BoxError>,
     {
         Failed.cause(error).into()
     }
+
+    /// Makes a new `Failure` instance which was caused by the trackable `error`.
+    ///
+    /// Unlike [`from_error`](#method.from_error), the history of `error` is moved into
+    /// the resulting `Failure`, so that the tracked locations are kept as structured entries.
+    /// The cause (i.e., `error` without its history) is shown in the alternate form of `Display`
+    /// (see [`TrackableError`](struct.TrackableError.html#display)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::Trackable;
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failure, TrackableError};
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    /// impl ErrorKind for NotFound {}
+    ///
+    /// let e: TrackableError<NotFound> = track!(NotFound.cause("no such user"));
+    /// let e = track!(Failure::from_trackable_error(e));
+    /// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+    /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+    /// Failed (cause; NotFound (cause; no such user))
+    ///   caused by: no such user
+    /// HISTORY:
+    ///   [0] at src/error.rs:13:35
+    ///   [1] at src/error.rs:14:9
+    /// "#);
+    /// # }
+    /// ```
+    pub fn from_trackable_error<E>(mut error: E) -> Self
+    where
+        E: Trackable<Event = Location> + Into<BoxError>,
+    {
+        let history = error.history_mut().map(mem::take);
+        let mut inner = CauseInner::new(error);
+        inner.alternate = true;
+        let mut failure = Failed.error();
+        failure.cause = Some(Cause::Shared(Arc::new(inner)));
+        if let Some(history) = history {
+            failure.history = history;
+        }
+        failure.into()
+    }
+
+    /// Makes a new `Failure` instance from a boxed error.
+    ///
+    /// If `error` is a `Failure`, it is returned as it is (i.e., the history is preserved).
+    /// Otherwise this is equivalent to `Failure::from_error(error)`.
+    ///
+    /// Note that the opposite conversion is provided by the standard `From` implementation
+    /// for `Box<dyn Error + Send + Sync>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{BoxError, Failure};
+    ///
+    /// let e: BoxError = "something wrong".into();
+    /// let e = Failure::from_boxed(e);
+    /// assert_eq!(e.to_string(), "Failed (cause; something wrong)\nHISTORY:\n");
+    ///
+    /// let e: BoxError = e.into();
+    /// let e = Failure::from_boxed(e);
+    /// assert_eq!(e.to_string(), "Failed (cause; something wrong)\nHISTORY:\n");
+    /// ```
+    pub fn from_boxed(error: BoxError) -> Self {
+        match error.downcast::<Failure>() {
+            Ok(failure) => *failure,
+            Err(error) => Failure::from_error(error),
+        }
+    }
+
+    /// Makes a new `Failure` instance from an `anyhow::Error`.
+    ///
+    /// If `error` wraps a `Failure`, it is returned as it is (i.e., the history is preserved).
+    /// Otherwise `error` becomes the cause of the resulting `Failure`.
+    ///
+    /// This is available only if the `anyhow` feature is enabled.
+    /// Note that the opposite conversion is provided by the `From` implementation of `anyhow`,
+    /// and the `Display` form of the resulting `anyhow::Error` includes the history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// # extern crate anyhow;
+    /// #
+    /// # fn main() {
+    /// use trackable::error::{Failed, Failure, ErrorKindExt};
+    ///
+    /// let e: Failure = track!(Failed.cause("something wrong")).into();
+    /// let e = anyhow::Error::from(e);
+    /// let e = Failure::from_anyhow(e);
+    /// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+    /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+    /// Failed (cause; something wrong)
+    /// HISTORY:
+    ///   [0] at src/error.rs:8:18
+    /// "#);
+    ///
+    /// let e = Failure::from_anyhow(anyhow::anyhow!("oops"));
+    /// assert_eq!(e.to_string(), "Failed (cause; oops)\nHISTORY:\n");
+    /// # }
+    /// ```
+    #[cfg(feature = "anyhow")]
+    pub fn from_anyhow(error: anyhow::Error) -> Self {
+        match error.downcast::<Failure>() {
+            Ok(failure) => failure,
+            Err(error) => Failure::from_error(BoxError::from(error)),
+        }
+    }
+
+    /// Makes a new `Failure` instance which represents a timeout.
+    ///
+    /// The cause of the resulting `Failure` is a [`Timeout`](struct.Timeout.html)
+    /// that has `message`, so `is_timeout()` of it returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::Failure;
+    ///
+    /// let e = Failure::timeout("no response in 30s");
+    /// assert!(e.is_timeout());
+    /// assert_eq!(e.to_string(), "Failed (cause; timed out: no response in 30s)\nHISTORY:\n");
+    /// assert!(!Failure::from("no response").is_timeout());
+    /// ```
+    pub fn timeout<M>(message: M) -> Self
+    where
+        M: Into<Cow<'static, str>>,
+    {
+        Failure::from_error(Timeout::new(message))
+    }
+
+    /// Parses the `Display` form of a `Failure`.
+    ///
+    /// This is the inverse of `Display` for a `Failure` that has at most one single-line cause,
+    /// and is intended for tools that replay logs.
+    /// The cause of the resulting `Failure` is the parsed string, and the module paths of
+    /// the locations in its history are empty (as they are not shown in the `Display` form).
+    ///
+    /// # Grammar
+    ///
+    /// ```text
+    /// failure  = header "\n" "HISTORY:\n" [elided] *entry
+    /// header   = "Failed" [" (cause; " CAUSE ")"]
+    /// elided   = "  ... " COUNT " earlier entries elided\n"
+    /// entry    = "  [" INDEX "] at " FILE ":" LINE [":" COLUMN] [" [" FUNCTION "]"] [" (x" COUNT ")"]
+    ///            [" -- " MESSAGE] "\n" *continuation
+    /// continuation = [INDENT LINE] "\n"
+    /// ```
+    ///
+    /// - `INDEX` must be the sequence number of the entry (i.e., it starts from the elided `COUNT`).
+    /// - A `continuation` is a subsequent line of a multi-line `MESSAGE`,
+    ///   and `INDENT` consists of spaces as wide as the `"  [" INDEX "] "` part of the entry.
+    /// - The trailing newline of the input is optional.
+    ///
+    /// The `CAUSES:` and `CONTEXT:` sections, `caused by:` lines, tags of locations, and the annotations of
+    /// the `time`, `thread` and `tracing` features are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::Failure;
+    ///
+    /// let s = r#"Failed (cause; something wrong)
+    /// HISTORY:
+    ///   [0] at src/foo.rs:10:5
+    ///   [1] at src/bar.rs:20 [bar::run] -- retrying
+    /// "#;
+    /// let e = Failure::parse_display(s).unwrap();
+    /// # if cfg!(feature = "thread") { return; }
+    /// assert_eq!(e.to_string(), s);
+    ///
+    /// let e = Failure::parse_display("Failed\nCONTEXT:\n  id: 1\nHISTORY:\n").unwrap_err();
+    /// assert_eq!(e.line(), 2);
+    /// ```
+    pub fn parse_display(s: &str) -> Result<Self, ParseError> {
+        let s = s.strip_suffix('\n').unwrap_or(s);
+        let mut lines = s.split('\n');
+        let header = lines.next().unwrap_or("");
+        let cause = if header == "Failed" {
+            None
+        } else {
+            let cause = header
+                .strip_prefix("Failed (cause; ")
+                .and_then(|c| c.strip_suffix(')'))
+                .ok_or(ParseError::new(1, "invalid header line"))?;
+            Some(cause)
+        };
+        if lines.next() != Some("HISTORY:") {
+            return Err(ParseError::new(2, "`HISTORY:` is expected"));
+        }
+        let history =
+            History::parse_entries(lines).map_err(|(i, reason)| ParseError::new(i + 3, reason))?;
+        let e = match cause {
+            Some(cause) => Failed.cause(String::from(cause)),
+            None => Failed.error(),
+        };
+        Ok(Failure(e.with_history(history)))
+    }
+}
+
+impl<'a> From<&'a str> for Failure {
+    /// Makes a new `Failure` instance which was caused by the `message`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::Failure;
+    ///
+    /// fn foo() -> Result<(), Failure> {
+    ///     Err("something broke".into())
+    /// }
+    /// assert_eq!(foo().unwrap_err().to_string(), "Failed (cause; something broke)\nHISTORY:\n");
+    /// ```
+    fn from(message: &'a str) -> Self {
+        Failure::from_error(message)
+    }
+}
+impl From<String> for Failure {
+    /// Makes a new `Failure` instance which was caused by the `message`.
+    fn from(message: String) -> Self {
+        Failure::from_error(message)
+    }
+}
+impl Failure {
+    fn cmp_causes(&self, other: &Self) -> cmp::Ordering {
+        let mut a = self.0.causes();
+        let mut b = other.0.causes();
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => return cmp::Ordering::Equal,
+                (None, Some(_)) => return cmp::Ordering::Less,
+                (Some(_), None) => return cmp::Ordering::Greater,
+                (Some(x), Some(y)) => match x.to_string().cmp(&y.to_string()) {
+                    cmp::Ordering::Equal => {}
+                    ordering => return ordering,
+                },
+            }
+        }
+    }
+}
+
+/// `Failure`s are ordered from the most severe one, and then by the `Display` strings of their causes.
+///
+/// Two `Failure`s are equal if they have the same severity and causes.
+/// Note that their histories are not taken into account.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::Failure;
+///
+/// let mut failures = vec![Failure::from("bar"), track!(Failure::from("foo")), Failure::from("baz")];
+/// failures.sort();
+/// assert_eq!(failures.iter().map(|f| format!("{:#}", f)).collect::<Vec<_>>(), [
+///     "Failed (cause; bar)",
+///     "Failed (cause; baz)",
+///     "Failed (cause; foo)",
+/// ]);
+/// assert_eq!(Failure::from("foo"), track!(Failure::from("foo")));
+/// # }
+/// ```
+impl PartialEq for Failure {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.severity() == other.0.severity() && self.cmp_causes(other) == cmp::Ordering::Equal
+    }
+}
+impl Eq for Failure {}
+impl PartialOrd for Failure {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Failure {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        cmp::Reverse(self.0.severity())
+            .cmp(&cmp::Reverse(other.0.severity()))
+            .then_with(|| self.cmp_causes(other))
+    }
+}
+#[cfg(feature = "std")]
+impl From<io::Error> for Failure {
+    /// Makes a new `Failure` instance which was caused by the I/O `error`.
+    ///
+    /// The error is kept as it is (i.e., not stringified), so its kind can be recovered
+    /// by `concrete_cause`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use trackable::error::Failure;
+    ///
+    /// fn open() -> Result<std::fs::File, Failure> {
+    ///     Ok(std::fs::File::open("/path/to/non_existent_file")?)
+    /// }
+    ///
+    /// let e = open().unwrap_err();
+    /// let cause = e.concrete_cause::<io::Error>().unwrap();
+    /// assert_eq!(cause.kind(), io::ErrorKind::NotFound);
+    /// ```
+    fn from(error: io::Error) -> Self {
+        Failure::from_error(error)
+    }
+}
+
+/// An error which represents a timeout.
+///
+/// This is used as the cause of the `Failure` made by [`Failure::timeout`](struct.Failure.html#method.timeout),
+/// and makes [`TrackableError::is_timeout`](struct.TrackableError.html#method.is_timeout) return `true`.
+#[derive(Debug, Clone)]
+pub struct Timeout(Cow<'static, str>);
+impl Timeout {
+    /// Makes a new `Timeout` instance.
+    pub fn new<M>(message: M) -> Self
+    where
+        M: Into<Cow<'static, str>>,
+    {
+        Timeout(message.into())
+    }
+
+    /// Returns the message of this timeout.
+    pub fn message(&self) -> &str {
+        &self.0
+    }
 }
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "timed out")
+        } else {
+            write!(f, "timed out: {}", self.0)
+        }
+    }
+}
+impl Error for Timeout {}
+
+/// An error which can be returned when parsing the `Display` form of a `Failure`.
+///
+/// This is returned by [`Failure::parse_display`](struct.Failure.html#method.parse_display).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    reason: &'static str,
+}
+impl ParseError {
+    fn new(line: usize, reason: &'static str) -> Self {
+        ParseError { line, reason }
+    }
+
+    /// Returns the (1-origin) line number where the error was detected.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the reason of the error.
+    pub fn reason(&self) -> &str {
+        self.reason
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot parse line {}: {}", self.line, self.reason)
+    }
+}
+impl Error for ParseError {}
 
 /// A variant of `std::io::Error` that implements `Trackable` trait.
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, TrackableError)]
 #[trackable(error_kind = "io::ErrorKind")]
 pub struct IoError(TrackableError<io::ErrorKind>);
+#[cfg(feature = "std")]
 impl From<IoError> for io::Error {
     fn from(f: IoError) -> Self {
         io::Error::new(*f.kind(), f)
     }
 }
+#[cfg(feature = "std")]
 impl From<io::Error> for IoError {
     fn from(f: io::Error) -> Self {
         f.kind().cause(f).into()
     }
 }
+#[cfg(feature = "std")]
 impl From<Failure> for IoError {
     fn from(f: Failure) -> Self {
         io::ErrorKind::Other.takes_over(f).into()
     }
 }
+#[cfg(feature = "std")]
 impl ErrorKind for io::ErrorKind {
     fn description(&self) -> &str {
         "I/O Error"
     }
+    fn is_timeout(&self) -> bool {
+        *self == io::ErrorKind::TimedOut
+    }
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
 }
 
 /// An `Error` type for unit tests.
@@ -160,371 +572,4019 @@ impl Error for TopLevelError {
     }
 }
 
+/// The severity level of an error.
+///
+/// The variants are ordered from the least severe one (`Trace`) to the most severe one (`Critical`).
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+impl Default for Severity {
+    #[inline]
+    fn default() -> Self {
+        Severity::Error
+    }
+}
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+#[cfg(feature = "log")]
+impl From<Severity> for log::Level {
+    fn from(f: Severity) -> Self {
+        match f {
+            Severity::Trace => log::Level::Trace,
+            Severity::Debug => log::Level::Debug,
+            Severity::Info => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Error | Severity::Critical => log::Level::Error,
+        }
+    }
+}
+
+/// Emits a log record about the last location tracked in `error`.
+///
+/// This is used by [`track_log!`](../macro.track_log.html) macro.
+#[cfg(feature = "log")]
+#[doc(hidden)]
+pub fn log_tracked<E>(module_path: &str, severity: Severity, error: &E)
+where
+    E: Trackable<Event = Location> + fmt::Display,
+{
+    if let Some(location) = error.history().and_then(|h| h.events().last()) {
+        log::log!(target: module_path, severity.into(), "{}: {:#}", location, error);
+    }
+}
+
+const DEFAULT_DESCRIPTION: &str = "An error";
+
+/// The maximum number of the levels of a `source()` chain recorded by
+/// `TrackableError::from_error_chained`.
+const MAX_SOURCE_DEPTH: usize = 32;
+
+/// A helper trait to upcast a value to `&dyn Any`.
+///
+/// This is implemented for all `'static` types,
+/// and is used to downcast `ErrorKind` trait objects.
+pub trait AsAny {
+    /// Returns this value as `&dyn Any`.
+    fn as_any(&self) -> &dyn any::Any;
+}
+impl<T: any::Any> AsAny for T {
+    fn as_any(&self) -> &dyn any::Any {
+        self
+    }
+}
+
 /// This trait represents an error kind which `TrackableError` can have.
-pub trait ErrorKind: fmt::Debug {
+pub trait ErrorKind: fmt::Debug + AsAny {
     /// A short description of the error kind.
     ///
     /// This is used for the description of the error that contains it.
     ///
     /// The default implementation always returns `"An error"`.
     fn description(&self) -> &str {
-        "An error"
+        DEFAULT_DESCRIPTION
     }
 
-    /// Displays this kind.
+    /// The severity level of the error kind.
     ///
-    /// The default implementation uses the debugging form of this.
-    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-impl ErrorKind for String {
-    fn description(&self) -> &str {
-        self
-    }
-}
-
-/// An extention of `ErrorKind` trait.
-///
-/// This provides convenient functions to create a `TrackableError` instance of this kind.
-pub trait ErrorKindExt: ErrorKind + Sized {
-    /// Makes a `TrackableError` instance without cause.
+    /// If this is not `Severity::Error`, it will be shown in the header line of
+    /// the `Display` form of an error that contains it.
+    ///
+    /// The default implementation always returns `Severity::Error`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::error::Error;
-    /// use trackable::error::{Failed, ErrorKindExt};
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Severity};
     ///
-    /// let e = Failed.error();
-    /// assert!(e.cause().is_none());
+    /// #[derive(Debug)]
+    /// struct Timeout;
+    /// impl ErrorKind for Timeout {
+    ///     fn severity(&self) -> Severity {
+    ///         Severity::Warning
+    ///     }
+    /// }
+    ///
+    /// let e = Timeout.cause("no response");
+    /// assert_eq!(e.severity(), Severity::Warning);
+    /// assert_eq!(e.to_string(), "Timeout (severity; Warning) (cause; no response)\nHISTORY:\n");
     /// ```
-    #[inline]
-    fn error(self) -> TrackableError<Self> {
-        self.into()
+    fn severity(&self) -> Severity {
+        Severity::Error
     }
 
-    /// Makes a `TrackableError` instance with the specified `cause`.
+    /// Returns `true` if the operation that caused an error of this kind may succeed by retrying it,
+    /// otherwise `false`.
+    ///
+    /// The default implementation always returns `false`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::error::Error;
-    /// use trackable::error::{Failed, ErrorKindExt};
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failed};
     ///
-    /// let e = Failed.cause("something wrong");
-    /// assert_eq!(e.cause().unwrap().to_string(), "something wrong");
+    /// #[derive(Debug)]
+    /// struct Unavailable;
+    /// impl ErrorKind for Unavailable {
+    ///     fn is_retriable(&self) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let e = Unavailable.cause("service unavailable");
+    /// assert!(e.is_retriable());
+    /// assert!(!Failed.takes_over(e).is_retriable());
     /// ```
-    #[inline]
-    fn cause<E>(self, cause: E) -> TrackableError<Self>
-    where
-        E: Into<BoxError>,
-    {
-        TrackableError::new(self, cause.into())
+    fn is_retriable(&self) -> bool {
+        false
     }
 
-    /// Takes over from other `TrackableError` instance.
+    /// Returns `true` if the error kind represents a timeout, otherwise `false`.
     ///
-    /// The history of `from` will be preserved.
+    /// This is useful for generic retry or backoff logic.
+    ///
+    /// The default implementation always returns `false`.
     ///
     /// # Examples
     ///
     /// ```
-    /// # #[macro_use]
-    /// # extern crate trackable;
-    /// #
     /// use trackable::error::{ErrorKind, ErrorKindExt};
     ///
     /// #[derive(Debug)]
-    /// struct Kind0;
-    /// impl ErrorKind for Kind0 {}
+    /// enum RpcErrorKind {
+    ///     DeadlineExceeded,
+    ///     Other,
+    /// }
+    /// impl ErrorKind for RpcErrorKind {
+    ///     fn is_timeout(&self) -> bool {
+    ///         match *self {
+    ///             RpcErrorKind::DeadlineExceeded => true,
+    ///             RpcErrorKind::Other => false,
+    ///         }
+    ///     }
+    /// }
     ///
-    /// #[derive(Debug)]
-    /// struct Kind1;
-    /// impl ErrorKind for Kind1 {}
+    /// assert!(RpcErrorKind::DeadlineExceeded.error().is_timeout());
+    /// assert!(!RpcErrorKind::Other.error().is_timeout());
+    /// ```
+    fn is_timeout(&self) -> bool {
+        false
+    }
+
+    /// The machine-readable code of the error kind.
     ///
-    /// fn main() {
-    ///   let e = Kind0.error();
-    ///   let e = track!(e);
+    /// If this is not `None`, it will be shown in the header line of
+    /// the `Display` form of an error that contains it, and included in
+    /// the serialized form of the error (if the `serialize` feature is enabled).
     ///
-    ///   let e = Kind1.takes_over(e);
-    ///   let e = track!(e);
+    /// The default implementation always returns `None`.
     ///
-    ///   assert_eq!(format!("\nERROR: {}", e).replace('\\', "/"), r#"
-    /// ERROR: Kind1
-    /// HISTORY:
-    ///   [0] at src/error.rs:17
-    ///   [1] at src/error.rs:20
-    /// "#);
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct Critical;
+    /// impl ErrorKind for Critical {
+    ///     fn code(&self) -> Option<&str> {
+    ///         Some("E1001")
+    ///     }
     /// }
+    ///
+    /// let e = Critical.cause("something wrong");
+    /// assert_eq!(e.code(), Some("E1001"));
+    /// assert_eq!(e.to_string(), "Critical [E1001] (cause; something wrong)\nHISTORY:\n");
     /// ```
-    fn takes_over<F, K>(self, from: F) -> TrackableError<Self>
-    where
-        F: Into<TrackableError<K>>,
-        K: ErrorKind + Send + Sync + 'static,
-    {
-        let from = from.into();
-        TrackableError {
-            kind: self,
-            cause: from.cause,
-            history: from.history,
-        }
+    fn code(&self) -> Option<&str> {
+        None
     }
-}
-impl<T: ErrorKind> ErrorKindExt for T {}
 
-/// Trackable error.
-///
-/// # Examples
-///
-/// Defines your own `Error` type.
-///
-/// ```
-/// #[macro_use]
-/// extern crate trackable;
-/// use trackable::error::{TrackableError, ErrorKind, ErrorKindExt};
-///
-/// #[derive(Debug, TrackableError)]
-/// #[trackable(error_kind = "MyErrorKind")]
-/// struct MyError(TrackableError<MyErrorKind>);
-/// impl From<std::io::Error> for MyError {
-///     fn from(f: std::io::Error) -> Self {
-///         // Any I/O errors are considered critical
-///         MyErrorKind::Critical.cause(f).into()
-///     }
-/// }
-///
-/// # #[allow(dead_code)]
-/// #[derive(Debug, PartialEq, Eq)]
-/// enum MyErrorKind {
-///     Critical,
-///     NonCritical,
-/// }
-/// impl ErrorKind for MyErrorKind {}
-///
-/// fn main() {
-///     // Tracks an error
-///     let error: MyError = MyErrorKind::Critical.cause("something wrong").into();
-///     let error = track!(error);
-///     let error = track!(error, "I passed here");
-///     assert_eq!(format!("\nError: {}", error).replace('\\', "/"), r#"
-/// Error: Critical (cause; something wrong)
-/// HISTORY:
-///   [0] at src/error.rs:27
-///   [1] at src/error.rs:28 -- I passed here
-/// "#);
-///
-///     // Tries to execute I/O operation
-///     let result = (|| -> Result<_, MyError> {
-///         let f = track!(std::fs::File::open("/path/to/non_existent_file")
-///                        .map_err(MyError::from))?;
-///         Ok(f)
-///     })();
-///     let error = result.err().unwrap();
-///     let cause = error.concrete_cause::<std::io::Error>().unwrap();
-///     assert_eq!(cause.kind(), std::io::ErrorKind::NotFound);
-/// }
-/// ```
-///
-/// `TrackableError` is cloneable if `K` is so.
-///
-/// ```no_run
-/// #[macro_use]
-/// extern crate trackable;
-///
-/// use trackable::Trackable;
-/// use trackable::error::{Failed, ErrorKindExt};
-///
-/// fn main() {
-///     let mut original = Failed.error();
-///
-///     let original = track!(original, "Hello `original`!");
-///     let forked = original.clone();
-///     let forked = track!(forked, "Hello `forked`!");
-///
-///     assert_eq!(format!("\n{}", original).replace('\\', "/"), r#"
-/// Failed
-/// HISTORY:
-///   [0] at src/error.rs:11 -- Hello `original`!
-/// "#);
-///
-///     assert_eq!(format!("\n{}", forked).replace('\\', "/"), r#"
-/// Failed
-/// HISTORY:
-///   [0] at src/error.rs:11 -- Hello `original`!
-///   [1] at src/error.rs:13 -- Hello `forked`!
-/// "#);
-/// }
-/// ```
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-pub struct TrackableError<K> {
-    kind: K,
-    cause: Option<Cause>,
-    history: History,
+    /// The HTTP status code corresponding to the error kind.
+    ///
+    /// This is useful to map errors to responses in web services,
+    /// without depending on any specific web framework.
+    ///
+    /// The default implementation always returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failed};
+    ///
+    /// #[derive(Debug)]
+    /// enum ApiErrorKind {
+    ///     NotFound,
+    ///     Other,
+    /// }
+    /// impl ErrorKind for ApiErrorKind {
+    ///     fn http_status(&self) -> Option<u16> {
+    ///         match *self {
+    ///             ApiErrorKind::NotFound => Some(404),
+    ///             ApiErrorKind::Other => Some(500),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let e = ApiErrorKind::NotFound.cause("no such user");
+    /// assert_eq!(e.http_status(), Some(404));
+    /// assert_eq!(ApiErrorKind::Other.takes_over(e).http_status(), Some(500));
+    /// assert_eq!(Failed.error().http_status(), None);
+    /// ```
+    fn http_status(&self) -> Option<u16> {
+        None
+    }
+
+    /// A message of the error kind to be shown to end users.
+    ///
+    /// Unlike the [description](#method.description), which is intended for developers,
+    /// this can be a polished (and possibly localized) message.
+    /// If this is not `None`, it is used instead of the kind in the alternate form of
+    /// `Display` (i.e., `{:#}`) of an error that contains it.
+    ///
+    /// The default implementation always returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct QuotaExceeded;
+    /// impl ErrorKind for QuotaExceeded {
+    ///     fn description(&self) -> &str {
+    ///         "quota exceeded"
+    ///     }
+    ///     fn user_message(&self) -> Option<String> {
+    ///         Some("You have reached your storage limit.".to_owned())
+    ///     }
+    /// }
+    ///
+    /// let e = QuotaExceeded.cause("used=1024");
+    /// assert_eq!(e.user_message().as_ref().map(|m| m.as_str()), Some("You have reached your storage limit."));
+    /// assert_eq!(format!("{:#}", e), "You have reached your storage limit. (cause; used=1024)");
+    /// assert_eq!(e.to_string(), "quota exceeded (cause; used=1024)\nHISTORY:\n");
+    /// ```
+    fn user_message(&self) -> Option<String> {
+        None
+    }
+
+    /// A suggestion to remedy the error kind (e.g., `"check your credentials"`).
+    ///
+    /// If this is not `None`, it is shown as a `suggestion: ...` line at the end of
+    /// the alternate form of `Display` (i.e., `{:#}`) of an error that contains it.
+    ///
+    /// The default implementation always returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct Unauthorized;
+    /// impl ErrorKind for Unauthorized {
+    ///     fn suggestion(&self) -> Option<&str> {
+    ///         Some("check your credentials")
+    ///     }
+    /// }
+    ///
+    /// let e = Unauthorized.cause("invalid token");
+    /// assert_eq!(e.suggestion(), Some("check your credentials"));
+    /// assert_eq!(format!("{:#}", e), "Unauthorized (cause; invalid token)\nsuggestion: check your credentials");
+    /// ```
+    fn suggestion(&self) -> Option<&str> {
+        None
+    }
+
+    /// Displays this kind.
+    ///
+    /// The default implementation writes the [description](#method.description) of this kind
+    /// if it is overridden (i.e., it is not `"An error"`), otherwise uses the debugging form of this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    /// impl ErrorKind for NotFound {}
+    ///
+    /// #[derive(Debug)]
+    /// struct Unauthorized;
+    /// impl ErrorKind for Unauthorized {
+    ///     fn description(&self) -> &str {
+    ///         "Unauthorized access"
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(NotFound.error().to_string(), "NotFound\nHISTORY:\n");
+    /// assert_eq!(Unauthorized.error().to_string(), "Unauthorized access\nHISTORY:\n");
+    /// ```
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = self.description();
+        if description == DEFAULT_DESCRIPTION {
+            write!(f, "{:?}", self)
+        } else {
+            f.write_str(description)
+        }
+    }
 }
-impl<K: ErrorKind> TrackableError<K> {
-    /// Makes a new `TrackableError` instance.
-    pub fn new<E>(kind: K, cause: E) -> Self
+impl dyn ErrorKind + Send + Sync {
+    /// Returns `true` if the concrete type of this kind is `K`, otherwise `false`.
+    pub fn is<K: ErrorKind + 'static>(&self) -> bool {
+        self.as_any().is::<K>()
+    }
+
+    /// Returns a reference to the concrete kind if it is of type `K`, otherwise `None`.
+    pub fn downcast_ref<K: ErrorKind + 'static>(&self) -> Option<&K> {
+        self.as_any().downcast_ref()
+    }
+}
+impl ErrorKind for BoxErrorKind {
+    fn description(&self) -> &str {
+        (**self).description()
+    }
+    fn severity(&self) -> Severity {
+        (**self).severity()
+    }
+    fn is_retriable(&self) -> bool {
+        (**self).is_retriable()
+    }
+    fn is_timeout(&self) -> bool {
+        (**self).is_timeout()
+    }
+    fn code(&self) -> Option<&str> {
+        (**self).code()
+    }
+    fn http_status(&self) -> Option<u16> {
+        (**self).http_status()
+    }
+    fn user_message(&self) -> Option<String> {
+        (**self).user_message()
+    }
+    fn suggestion(&self) -> Option<&str> {
+        (**self).suggestion()
+    }
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).display(f)
+    }
+}
+impl ErrorKind for String {
+    fn description(&self) -> &str {
+        self
+    }
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// An extention of `ErrorKind` trait.
+///
+/// This provides convenient functions to create a `TrackableError` instance of this kind.
+pub trait ErrorKindExt: ErrorKind + Sized {
+    /// Makes a `TrackableError` instance without cause.
+    ///
+    /// This does not allocate any heap memory
+    /// (unless a backtrace is captured by the `backtrace` feature),
+    /// so it is cheap to create an error that is never tracked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.error();
+    /// assert!(e.cause().is_none());
+    /// ```
+    ///
+    /// The resulting error must be used.
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// Failed.error(); // error: unused return value
+    /// ```
+    #[inline]
+    #[must_use]
+    fn error(self) -> TrackableError<Self> {
+        self.into()
+    }
+
+    /// Makes a `TrackableError` instance without cause, whose history starts with `location`.
+    ///
+    /// This is useful to create an error on behalf of another location
+    /// (e.g., to reconstruct an error from the metadata of a remote procedure call).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::{Location, Trackable};
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.error_at(Location::new("remote", "src/server.rs", 42, "rpc"));
+    /// assert_eq!(e.history().unwrap().first().map(|l| l.line()), Some(42));
+    /// # if cfg!(feature = "thread") { return; }
+    /// assert_eq!(e.to_string(), "Failed\nHISTORY:\n  [0] at src/server.rs:42 -- rpc\n");
+    /// ```
+    #[must_use]
+    fn error_at(self, location: Location) -> TrackableError<Self> {
+        let mut e = self.error();
+        e.history.add(location);
+        e
+    }
+
+    /// Makes a `TrackableError` instance with the specified `cause`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause("something wrong");
+    /// assert_eq!(e.cause().unwrap().to_string(), "something wrong");
+    /// ```
+    #[inline]
+    #[must_use]
+    fn cause<E>(self, cause: E) -> TrackableError<Self>
     where
         E: Into<BoxError>,
     {
-        TrackableError {
-            kind,
-            cause: Some(Cause(Arc::new(cause.into()))),
-            history: History::new(),
+        TrackableError::new(self, cause)
+    }
+
+    /// Makes a `TrackableError` instance with the already boxed `cause`.
+    ///
+    /// The box is stored as it is (i.e., it is not wrapped again),
+    /// so the concrete type of the boxed error can still be retrieved by
+    /// [`TrackableError::concrete_cause`](struct.TrackableError.html#method.concrete_cause).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use trackable::error::{BoxError, Failed, ErrorKindExt};
+    ///
+    /// let cause: BoxError = Box::new(io::Error::new(io::ErrorKind::Other, "oops"));
+    /// let e = Failed.cause_boxed(cause);
+    /// assert_eq!(e.concrete_cause::<io::Error>().map(|e| e.kind()), Some(io::ErrorKind::Other));
+    /// ```
+    #[inline]
+    #[must_use]
+    fn cause_boxed(self, cause: BoxError) -> TrackableError<Self> {
+        TrackableError::new(self, cause)
+    }
+
+    /// Makes a `TrackableError` instance with the cause returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let value: Option<u32> = None;
+    /// let e = value.ok_or_else(|| Failed.cause_with(|| format!("no value: {:?}", value)));
+    /// assert_eq!(e.err().unwrap().to_string(), "Failed (cause; no value: None)\nHISTORY:\n");
+    /// ```
+    #[inline]
+    #[must_use]
+    fn cause_with<E, F>(self, f: F) -> TrackableError<Self>
+    where
+        E: Into<BoxError>,
+        F: FnOnce() -> E,
+    {
+        self.cause(f())
+    }
+
+    /// Makes an `Err` value that has a `TrackableError` instance with the cause returned by `f`.
+    ///
+    /// This is the `Result` returning variant of [`cause_with`](#method.cause_with).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{Failed, ErrorKindExt, TrackableError};
+    ///
+    /// fn div(a: u32, b: u32) -> Result<u32, TrackableError<Failed>> {
+    ///     if b == 0 {
+    ///         return Failed.fail_with(|| format!("{} is divided by zero", a));
+    ///     }
+    ///     Ok(a / b)
+    /// }
+    ///
+    /// assert_eq!(div(4, 2).ok(), Some(2));
+    /// assert_eq!(div(4, 0).err().unwrap().to_string(),
+    ///            "Failed (cause; 4 is divided by zero)\nHISTORY:\n");
+    /// ```
+    #[inline]
+    #[cfg_attr(feature = "smallvec", allow(clippy::result_large_err))]
+    fn fail_with<T, E, F>(self, f: F) -> Result<T, TrackableError<Self>>
+    where
+        E: Into<BoxError>,
+        F: FnOnce() -> E,
+    {
+        Err(self.cause_with(f))
+    }
+
+    /// Makes a `TrackableError` instance that wraps the lower-level error `e` as its cause.
+    ///
+    /// This is the same as [`cause`](#method.cause), but intended for the case where `e` is
+    /// an error of a lower layer. Unlike [`takes_over`](#method.takes_over),
+    /// the resulting error starts with an empty history and `e` is kept as is,
+    /// so that it can be reached by `source()` or `concrete_cause()`.
+    /// If `e` is a `TrackableError`, its history is preserved in its `Display` form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// use std::error::Error;
+    /// use trackable::Trackable;
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failed, Failure};
+    ///
+    /// #[derive(Debug)]
+    /// struct Storage;
+    /// impl ErrorKind for Storage {}
+    ///
+    /// let e: Failure = track!(Failed.cause("disk full").into());
+    /// let e = Storage.wrap(e);
+    /// assert!(e.history().unwrap().is_empty());
+    ///
+    /// # if cfg!(feature = "disable_tracking") { return; }
+    /// let original = e.concrete_cause::<Failure>().unwrap();
+    /// assert_eq!(original.history().unwrap().len(), 1);
+    /// assert!(e.source().unwrap().to_string().starts_with("Failed (cause; disk full)\nHISTORY:\n  [0] at "));
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    fn wrap<E>(self, e: E) -> TrackableError<Self>
+    where
+        E: Into<BoxError>,
+    {
+        self.cause(e)
+    }
+
+    /// Takes over from other `TrackableError` instance.
+    ///
+    /// The history of `from` will be preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct Kind0;
+    /// impl ErrorKind for Kind0 {}
+    ///
+    /// #[derive(Debug)]
+    /// struct Kind1;
+    /// impl ErrorKind for Kind1 {}
+    ///
+    /// fn main() {
+    /// #   std::env::remove_var("RUST_BACKTRACE");
+    ///   let e = Kind0.error();
+    ///   let e = track!(e);
+    ///
+    ///   let e = Kind1.takes_over(e);
+    ///   let e = track!(e);
+    ///
+    /// #   if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+    ///   assert_eq!(format!("\nERROR: {}", e).replace('\\', "/"), r#"
+    /// ERROR: Kind1
+    /// HISTORY:
+    ///   [0] at src/error.rs:17:11
+    ///   [1] at src/error.rs:20:11
+    /// "#);
+    /// }
+    /// ```
+    fn takes_over<F, K>(self, from: F) -> TrackableError<Self>
+    where
+        F: Into<TrackableError<K>>,
+        K: ErrorKind + Send + Sync + 'static,
+    {
+        from.into().map_kind(|_| self)
+    }
+
+    /// Converts this kind into an `Error` object.
+    ///
+    /// Unlike [`error`](#method.error), the resulting object has no cause and history,
+    /// and is intended to be used where a plain `std::error::Error` is expected
+    /// (e.g., `Box<dyn Error>`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e: Box<dyn Error> = Box::new(Failed.into_std_error());
+    /// assert_eq!(e.to_string(), "Failed");
+    /// ```
+    #[inline]
+    #[must_use]
+    fn into_std_error(self) -> ErrorKindError<Self> {
+        ErrorKindError(self)
+    }
+}
+impl<T: ErrorKind> ErrorKindExt for T {}
+
+/// An adapter that implements `std::error::Error` for an `ErrorKind`.
+///
+/// The `Display` form of this is the same as the one of the kind in the header line of `TrackableError`,
+/// and the `description` is delegated to the kind.
+///
+/// This is created by [`ErrorKindExt::into_std_error`](trait.ErrorKindExt.html#method.into_std_error).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorKindError<K>(K);
+impl<K: ErrorKind> ErrorKindError<K> {
+    /// Returns a reference to the kind.
+    pub fn kind(&self) -> &K {
+        &self.0
+    }
+
+    /// Converts this into the kind.
+    pub fn into_kind(self) -> K {
+        self.0
+    }
+}
+impl<K: ErrorKind> From<K> for ErrorKindError<K> {
+    fn from(f: K) -> Self {
+        ErrorKindError(f)
+    }
+}
+impl<K: ErrorKind> fmt::Display for ErrorKindError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.display(f)
+    }
+}
+impl<K: ErrorKind> Error for ErrorKindError<K> {
+    fn description(&self) -> &str {
+        self.0.description()
+    }
+}
+
+/// Trackable error.
+///
+/// # Examples
+///
+/// Defines your own `Error` type.
+///
+/// ```
+/// #[macro_use]
+/// extern crate trackable;
+/// use trackable::error::{TrackableError, ErrorKind, ErrorKindExt};
+///
+/// #[derive(Debug, TrackableError)]
+/// #[trackable(error_kind = "MyErrorKind")]
+/// struct MyError(TrackableError<MyErrorKind>);
+/// impl From<std::io::Error> for MyError {
+///     fn from(f: std::io::Error) -> Self {
+///         // Any I/O errors are considered critical
+///         MyErrorKind::Critical.cause(f).into()
+///     }
+/// }
+///
+/// # #[allow(dead_code)]
+/// #[derive(Debug, PartialEq, Eq)]
+/// enum MyErrorKind {
+///     Critical,
+///     NonCritical,
+/// }
+/// impl ErrorKind for MyErrorKind {}
+///
+/// fn main() {
+/// #     std::env::remove_var("RUST_BACKTRACE");
+///     // Tracks an error
+///     let error: MyError = MyErrorKind::Critical.cause("something wrong").into();
+///     let error = track!(error);
+///     let error = track!(error, "I passed here");
+/// #     if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+///     assert_eq!(format!("\nError: {}", error).replace('\\', "/"), r#"
+/// Error: Critical (cause; something wrong)
+/// HISTORY:
+///   [0] at src/error.rs:28:17
+///   [1] at src/error.rs:29:17 -- I passed here
+/// "#);
+///
+///     // Tries to execute I/O operation
+///     let result = (|| -> Result<_, MyError> {
+///         let f = track!(std::fs::File::open("/path/to/non_existent_file")
+///                        .map_err(MyError::from))?;
+///         Ok(f)
+///     })();
+///     let error = result.err().unwrap();
+///     let cause = error.concrete_cause::<std::io::Error>().unwrap();
+///     assert_eq!(cause.kind(), std::io::ErrorKind::NotFound);
+/// }
+/// ```
+///
+/// `TrackableError` is cloneable if `K` is so.
+///
+/// ```no_run
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// fn main() {
+///     let mut original = Failed.error();
+///
+///     let original = track!(original, "Hello `original`!");
+///     let forked = original.clone();
+///     let forked = track!(forked, "Hello `forked`!");
+///
+///     assert_eq!(format!("\n{}", original).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/error.rs:10:20 -- Hello `original`!
+/// "#);
+///
+///     assert_eq!(format!("\n{}", forked).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/error.rs:10:20 -- Hello `original`!
+///   [1] at src/error.rs:12:18 -- Hello `forked`!
+/// "#);
+/// }
+/// ```
+///
+/// # Serialization
+///
+/// If the `serialize` feature is enabled, `TrackableError` is serialized as an object
+/// which has the following fields:
+///
+/// - `kind`: the serialized form of `K`
+/// - `code`: the [code](trait.ErrorKind.html#method.code) of the kind (omitted if `None`)
+/// - `cause`: the primary cause, or `null` if there is no cause
+/// - `additional_causes`: the other causes (omitted if empty)
+/// - `context`: a map of the key-value pairs attached by `with_context` (omitted if empty)
+/// - `version`: the version stamped by [`stamp_version`](#method.stamp_version) (omitted if `None`)
+/// - `history_len`: the number of the entries in `history` (ignored when deserializing)
+/// - `history`: an array of the tracked [`Location`](../struct.Location.html) objects
+///
+/// For example:
+///
+/// ```json
+/// {
+///   "kind": null,
+///   "cause": {"type": "&str", "message": "something wrong"},
+///   "history_len": 2,
+///   "history": [
+///     {"file": "src/foo.rs", "line": 10, "column": 5, "module_path": "foo", "message": ""},
+///     {"file": "src/bar.rs", "line": 20, "column": 9, "module_path": "bar", "message": "Hi"}
+///   ]
+/// }
+/// ```
+///
+/// A cause is serialized as an object which has the `Display` string of the cause (`message`)
+/// and the name of its type (`type`, see [`cause_type_name`](#method.cause_type_name)).
+/// A plain string is also accepted as a cause when deserializing.
+///
+/// Note that the concrete types of the causes are lost by the serialization
+/// (only their names are kept).
+/// Long cause messages can be truncated by [`serialize_with_max_cause_len`](#method.serialize_with_max_cause_len).
+///
+/// # Display
+///
+/// If the error has a single cause, the `source()` chain of the cause is shown
+/// as `caused by:` lines beneath the header line.
+/// Each level is shown in the alternate form (i.e., `{:#}`),
+/// so that a nested trackable error takes a single line without its history.
+/// At most 32 levels are shown, and a `... (cause chain truncated)` line follows them
+/// if the chain is longer (e.g., cyclic).
+///
+/// ```
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use std::error::Error;
+/// use std::fmt;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// #[derive(Debug)]
+/// struct Outer(fmt::Error);
+/// impl fmt::Display for Outer {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "cannot format")
+///     }
+/// }
+/// impl Error for Outer {
+///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// let e = Failed.cause(Outer(fmt::Error));
+/// assert_eq!(format!("\n{}", e), r#"
+/// Failed (cause; cannot format)
+///   caused by: an error occurred when formatting an argument
+/// HISTORY:
+/// "#);
+/// ```
+///
+/// The alternate form of `Display` (i.e., `{:#}`) shows only the kind and the causes in a single line.
+/// If the kind has a [user message](trait.ErrorKind.html#method.user_message),
+/// it is shown instead of the kind.
+/// If the kind has a [suggestion](trait.ErrorKind.html#method.suggestion),
+/// it is shown in the following `suggestion: ...` line.
+///
+/// ```
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let mut e = Failed.cause("foo").with_context("id", 1);
+/// e.add_cause("bar");
+/// assert_eq!(format!("{:#}", e), "Failed (cause; foo) (cause; bar)");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(Deserialize))]
+pub struct TrackableError<K> {
+    kind: K,
+    cause: Option<Cause>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    additional_causes: Vec<Cause>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    context: Context,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    version: Option<Box<Cow<'static, str>>>, // Boxed to keep the error small
+    history: History,
+    #[cfg(feature = "backtrace")]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    backtrace: Option<Arc<Backtrace>>,
+}
+impl<K: ErrorKind> TrackableError<K> {
+    /// Makes a new `TrackableError` instance.
+    ///
+    /// If the `backtrace` feature is enabled and the `RUST_BACKTRACE` environment variable is set
+    /// (to a value other than `"0"`), a backtrace of the calling thread is also captured.
+    #[must_use]
+    pub fn new<E>(kind: K, cause: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        TrackableError {
+            kind,
+            cause: Some(Cause::new(cause)),
+            additional_causes: Vec::new(),
+            context: Context::default(),
+            version: None,
+            history: History::new(),
+            #[cfg(feature = "backtrace")]
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    /// Makes a new `TrackableError` instance that records the `source()` chain of `error` into its history.
+    ///
+    /// `error` becomes the cause of the resulting error, and each level of its `source()` chain
+    /// is added to the history as an entry with the message `caused by: ${source}`
+    /// (the location of each entry is the caller of this function).
+    /// At most 32 levels are recorded, so cyclic chains are also handled.
+    ///
+    /// Since the chain is kept in the history, the `Display` form of the resulting error
+    /// does not show it as `caused by:` lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use std::error::Error;
+    /// use std::fmt;
+    /// use trackable::error::{Failed, TrackableError};
+    ///
+    /// #[derive(Debug)]
+    /// struct Outer(fmt::Error);
+    /// impl fmt::Display for Outer {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "cannot format")
+    ///     }
+    /// }
+    /// impl Error for Outer {
+    ///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+    ///         Some(&self.0)
+    ///     }
+    /// }
+    ///
+    /// let e = TrackableError::from_error_chained(Failed, Outer(fmt::Error));
+    /// # if cfg!(feature = "thread") { return; }
+    /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+    /// Failed (cause; cannot format)
+    /// HISTORY:
+    ///   [0] at src/error.rs:23:9 -- caused by: an error occurred when formatting an argument
+    /// "#);
+    /// ```
+    #[track_caller]
+    #[must_use]
+    pub fn from_error_chained<E>(kind: K, error: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        let caller = panic::Location::caller();
+        let mut history = History::new();
+        let mut source = error.source();
+        while let Some(e) = source {
+            if history.len() == MAX_SOURCE_DEPTH {
+                break;
+            }
+            history.add(Location::from_caller(caller, format!("caused by: {:#}", e)));
+            source = e.source();
+        }
+        let mut inner = CauseInner::new(error);
+        inner.chain_in_history = true;
+        let mut this = TrackableError::from_kind(kind);
+        this.cause = Some(Cause::Shared(Arc::new(inner)));
+        this.history = history;
+        this
+    }
+
+    /// Makes a new `TrackableError` instance whose primary cause is not shared by clones.
+    ///
+    /// The causes of `TrackableError` are usually reference-counted by `Arc` so that errors can be cloned cheaply,
+    /// at the cost of the atomic operations on clone and drop.
+    /// The cause of the error returned by this function is held by `Box` instead,
+    /// which avoids the overhead in hot paths where errors are never cloned.
+    ///
+    /// The resulting error can still be cloned, but the cause of a clone is a copy of
+    /// the `Display` string of the original cause (so `concrete_cause` will not find it in the clone).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt;
+    /// use trackable::error::{Failed, TrackableError};
+    ///
+    /// let e = TrackableError::new_unshared(Failed, fmt::Error);
+    /// assert!(e.concrete_cause::<fmt::Error>().is_some());
+    ///
+    /// let cloned = e.clone();
+    /// assert!(cloned.concrete_cause::<fmt::Error>().is_none());
+    /// assert_eq!(cloned.to_string(), e.to_string());
+    /// ```
+    #[must_use]
+    pub fn new_unshared<E>(kind: K, cause: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        TrackableError {
+            kind,
+            cause: Some(Cause::new_unshared(cause)),
+            additional_causes: Vec::new(),
+            context: Context::default(),
+            version: None,
+            history: History::new(),
+            #[cfg(feature = "backtrace")]
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    /// Returns a builder to make a `TrackableError` instance of `kind` in one expression.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, TrackableError};
+    ///
+    /// let e = TrackableError::builder(Failed)
+    ///     .cause("something wrong")
+    ///     .context("id", 10)
+    ///     .track_here()
+    ///     .build();
+    /// assert_eq!(format!("{:#}", e), "Failed (cause; something wrong)");
+    /// assert_eq!(e.track_count(), 1);
+    /// ```
+    pub fn builder(kind: K) -> TrackableErrorBuilder<K> {
+        TrackableErrorBuilder(TrackableError::from_kind(kind))
+    }
+
+    /// Makes a new `TrackableError` instance from `kind`.
+    ///
+    /// Note that the returning error has no cause.
+    fn from_kind(kind: K) -> Self {
+        TrackableError {
+            kind,
+            cause: None,
+            additional_causes: Vec::new(),
+            context: Context::default(),
+            version: None,
+            history: History::new(),
+            #[cfg(feature = "backtrace")]
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    /// Returns the kind of this error.
+    #[inline]
+    pub fn kind(&self) -> &K {
+        &self.kind
+    }
+
+    /// Converts the kind of this error by applying `f` to it.
+    ///
+    /// The causes and the history of this error are preserved.
+    /// Unlike `ErrorKindExt::takes_over`, the new kind can be derived from the old one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// use trackable::Trackable;
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct Kind0(u32);
+    /// impl ErrorKind for Kind0 {}
+    ///
+    /// #[derive(Debug)]
+    /// struct Kind1(String);
+    /// impl ErrorKind for Kind1 {}
+    ///
+    /// # fn main() {
+    /// let e = track!(Kind0(404).cause("not found"));
+    /// let e = e.map_kind(|k| Kind1(k.0.to_string()));
+    /// # if cfg!(feature = "disable_tracking") { return; }
+    /// assert_eq!(e.kind().0, "404");
+    /// assert_eq!(e.history().unwrap().events().len(), 1);
+    /// # }
+    /// ```
+    pub fn map_kind<K2, F>(self, f: F) -> TrackableError<K2>
+    where
+        K2: ErrorKind,
+        F: FnOnce(K) -> K2,
+    {
+        TrackableError {
+            kind: f(self.kind),
+            cause: self.cause,
+            additional_causes: self.additional_causes,
+            context: self.context,
+            version: self.version,
+            history: self.history,
+            #[cfg(feature = "backtrace")]
+            backtrace: self.backtrace,
+        }
+    }
+
+    /// Erases the concrete kind of this error by boxing it.
+    ///
+    /// This is useful to handle errors that have different kinds uniformly.
+    /// The causes and the history of this error are preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{BoxErrorKind, ErrorKind, ErrorKindExt, Failed, TrackableError};
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    /// impl ErrorKind for NotFound {}
+    ///
+    /// let errors: Vec<TrackableError<BoxErrorKind>> = vec![
+    ///     Failed.cause("foo").into_boxed_kind(),
+    ///     NotFound.cause("bar").into_boxed_kind(),
+    /// ];
+    /// assert_eq!(errors[0].to_string(), "Failed (cause; foo)\nHISTORY:\n");
+    /// assert_eq!(errors[1].to_string(), "NotFound (cause; bar)\nHISTORY:\n");
+    /// ```
+    pub fn into_boxed_kind(self) -> TrackableError<BoxErrorKind>
+    where
+        K: Send + Sync + 'static,
+    {
+        self.map_kind(|k| Box::new(k) as BoxErrorKind)
+    }
+
+    /// Returns `true` if the kind of this error is of type `K2`, otherwise `false`.
+    ///
+    /// If the kind is a [`BoxErrorKind`](type.BoxErrorKind.html),
+    /// the concrete type of the boxed kind is checked instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failed};
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound;
+    /// impl ErrorKind for NotFound {}
+    ///
+    /// assert!(Failed.error().is::<Failed>());
+    /// assert!(!Failed.error().is::<NotFound>());
+    ///
+    /// let e = NotFound.error().into_boxed_kind();
+    /// assert!(e.is::<NotFound>());
+    /// assert!(!e.is::<Failed>());
+    /// ```
+    pub fn is<K2: ErrorKind + 'static>(&self) -> bool
+    where
+        K: 'static,
+    {
+        match self.kind.as_any().downcast_ref::<BoxErrorKind>() {
+            Some(kind) => kind.is::<K2>(),
+            None => self.kind.as_any().is::<K2>(),
+        }
+    }
+
+    /// Returns the severity level of this error.
+    ///
+    /// This is equivalent to `self.kind().severity()`.
+    #[inline]
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+
+    /// Returns `true` if the operation that caused this error may succeed by retrying it,
+    /// otherwise `false`.
+    ///
+    /// This is equivalent to `self.kind().is_retriable()`.
+    #[inline]
+    pub fn is_retriable(&self) -> bool {
+        self.kind.is_retriable()
+    }
+
+    /// Returns `true` if this error represents a timeout, otherwise `false`.
+    ///
+    /// This is `true` if the kind of this error is a timeout
+    /// (see [`ErrorKind::is_timeout`](trait.ErrorKind.html#method.is_timeout)),
+    /// or if any of the causes is a [`Timeout`](struct.Timeout.html)
+    /// (e.g., the error is made by [`Failure::timeout`](struct.Failure.html#method.timeout)).
+    pub fn is_timeout(&self) -> bool {
+        self.kind.is_timeout() || self.concrete_cause::<Timeout>().is_some()
+    }
+
+    /// Returns the machine-readable code of this error.
+    ///
+    /// This is equivalent to `self.kind().code()`.
+    #[inline]
+    pub fn code(&self) -> Option<&str> {
+        self.kind.code()
+    }
+
+    /// Returns the HTTP status code corresponding to this error.
+    ///
+    /// This is equivalent to `self.kind().http_status()`.
+    #[inline]
+    pub fn http_status(&self) -> Option<u16> {
+        self.kind.http_status()
+    }
+
+    /// Returns the message of this error to be shown to end users.
+    ///
+    /// This is equivalent to `self.kind().user_message()`.
+    #[inline]
+    pub fn user_message(&self) -> Option<String> {
+        self.kind.user_message()
+    }
+
+    /// Returns the suggestion to remedy this error.
+    ///
+    /// This is equivalent to `self.kind().suggestion()`.
+    #[inline]
+    pub fn suggestion(&self) -> Option<&str> {
+        self.kind.suggestion()
+    }
+
+    /// Returns the number of the locations this error has been tracked at.
+    ///
+    /// This is equivalent to `self.history().unwrap().len()` but is cheaper to call.
+    /// Note that the events elided by `History::set_max_len` are not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.error();
+    /// assert_eq!(e.track_count(), 0);
+    ///
+    /// let e = track!(track!(e));
+    /// # if cfg!(feature = "disable_tracking") { return; }
+    /// assert_eq!(e.track_count(), 2);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn track_count(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Clears the history of this error.
+    ///
+    /// The allocation of the history is retained for reuse
+    /// (see [`History::clear`](../struct.History.html#method.clear)).
+    /// The kind, the causes and the context of this error are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut e = track!(Failed.cause("something wrong"));
+    /// e.reset_history();
+    /// assert_eq!(e.track_count(), 0);
+    /// assert_eq!(e.to_string(), "Failed (cause; something wrong)\nHISTORY:\n");
+    /// # }
+    /// ```
+    pub fn reset_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Replaces the history of this error with `history`, and returns the previous one.
+    ///
+    /// This is useful to reconstruct an error from another representation
+    /// (e.g., a serialized form or another error).
+    /// Note that the existing history entries are not merged but discarded from this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    /// use trackable::error::{Failed, ErrorKindExt, History};
+    ///
+    /// let mut history = History::new();
+    /// history.add(Location::new("foo", "src/foo.rs", 10, "bar"));
+    ///
+    /// let mut e = Failed.error();
+    /// let old = e.set_history(history);
+    /// assert!(old.is_empty());
+    /// assert_eq!(e.track_count(), 1);
+    /// ```
+    pub fn set_history(&mut self, history: History) -> History {
+        mem::replace(&mut self.history, history)
+    }
+
+    /// Replaces the history of this error with `history`.
+    ///
+    /// This is a builder-style variant of [`set_history`](#method.set_history).
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Converts this error into a read-only error that can be shared cheaply.
+    ///
+    /// See [`FrozenError`](struct.FrozenError.html) for details.
+    pub fn freeze(self) -> FrozenError<K> {
+        FrozenError(Arc::new(self))
+    }
+
+    /// Returns the backtrace captured when this error was created.
+    ///
+    /// See the documentation of [`new`](#method.new) for the condition of capturing.
+    #[cfg(feature = "backtrace")]
+    #[inline]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_deref()
+    }
+
+    /// Adds a cause to this error.
+    ///
+    /// If this error has no cause, `cause` becomes the primary one
+    /// (i.e., the one returned by `Error::cause`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut e = Failed.cause("foo");
+    /// e.add_cause("bar");
+    ///
+    /// let causes = e.causes().map(|c| c.to_string()).collect::<Vec<_>>();
+    /// assert_eq!(causes, ["foo", "bar"]);
+    /// assert_eq!(format!("\n{}", e), r#"
+    /// Failed
+    /// CAUSES:
+    ///   [0] foo
+    ///   [1] bar
+    /// HISTORY:
+    /// "#);
+    /// ```
+    pub fn add_cause<E>(&mut self, cause: E)
+    where
+        E: Into<BoxError>,
+    {
+        let cause = Cause::new(cause);
+        if self.cause.is_none() {
+            self.cause = Some(cause);
+        } else {
+            self.additional_causes.push(cause);
+        }
+    }
+
+    /// Attaches a key-value pair to this error as a context.
+    ///
+    /// Contexts are shown in the `CONTEXT:` block of the `Display` form
+    /// and serialized as a map (if the `serialize` feature is enabled).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause("something wrong")
+    ///     .with_context("request_id", 42)
+    ///     .with_context("user_id", "alice");
+    ///
+    /// let context = e.context().collect::<Vec<_>>();
+    /// assert_eq!(context, [("request_id", "42"), ("user_id", "alice")]);
+    /// assert_eq!(format!("\n{}", e), r#"
+    /// Failed (cause; something wrong)
+    /// CONTEXT:
+    ///   request_id: 42
+    ///   user_id: alice
+    /// HISTORY:
+    /// "#);
+    /// ```
+    pub fn with_context<V>(mut self, key: &'static str, value: V) -> Self
+    where
+        V: fmt::Display,
+    {
+        self.context.0.push((Cow::Borrowed(key), value.to_string()));
+        self
+    }
+
+    /// Returns an iterator over the key-value pairs attached to this error by `with_context`.
+    pub fn context(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.context.0.iter().map(|(k, v)| (k.as_ref(), v.as_ref()))
+    }
+
+    /// Stamps the version (or build id) of the emitting crate on this error.
+    ///
+    /// The version is included in the serialized form of the error
+    /// (if the `serialize` feature is enabled), so that the line numbers in the history
+    /// can be correlated with the right source revision.
+    /// If a version has already been stamped, it is replaced with `version`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut e = Failed.cause("something wrong");
+    /// assert_eq!(e.version(), None);
+    ///
+    /// e.stamp_version(env!("CARGO_PKG_VERSION"));
+    /// assert_eq!(e.version(), Some(env!("CARGO_PKG_VERSION")));
+    /// ```
+    pub fn stamp_version(&mut self, version: &'static str) {
+        self.version = Some(Box::new(Cow::Borrowed(version)));
+    }
+
+    /// Returns the version stamped on this error by [`stamp_version`](#method.stamp_version).
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().map(|v| v.as_ref().as_ref())
+    }
+
+    /// Takes the primary cause out of this error.
+    ///
+    /// The cause can be taken only if it is held uniquely by this error
+    /// (this is always the case for errors made by [`new_unshared`](#method.new_unshared)).
+    /// If it is shared with other errors (i.e., this error has been cloned),
+    /// this method returns `None` and the cause is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut e = Failed.cause("something wrong");
+    /// let cloned = e.clone();
+    /// assert!(e.take_cause().is_none());
+    ///
+    /// drop(cloned);
+    /// assert_eq!(e.take_cause().unwrap().to_string(), "something wrong");
+    /// assert!(e.take_cause().is_none());
+    /// ```
+    pub fn take_cause(&mut self) -> Option<BoxError> {
+        match self.cause.take()? {
+            Cause::Shared(shared) => match Arc::try_unwrap(shared) {
+                Ok(inner) => Some(inner.error),
+                Err(shared) => {
+                    self.cause = Some(Cause::Shared(shared));
+                    None
+                }
+            },
+            Cause::Unshared(inner) => Some(inner.error),
+        }
+    }
+
+    /// Returns the type name of the primary cause of this error.
+    ///
+    /// The name is obtained by `std::any::type_name` when the cause is given,
+    /// so it is the name of the type passed to `new`, `add_cause` and so on
+    /// (e.g., `&str` if the cause is a string literal).
+    /// The name survives serialization (if the `serialize` feature is enabled).
+    ///
+    /// This returns `None` if there is no cause, or if the cause was deserialized from
+    /// a data that has no type name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause(std::fmt::Error);
+    /// assert_eq!(e.cause_type_name(), Some("core::fmt::Error"));
+    /// assert_eq!(Failed.error().cause_type_name(), None);
+    /// ```
+    pub fn cause_type_name(&self) -> Option<&str> {
+        self.cause
+            .as_ref()
+            .and_then(|c| c.inner().type_name.as_deref())
+    }
+
+    /// Merges `other` into this error.
+    ///
+    /// The history entries of `other` are appended after the existing ones of this error,
+    /// and `other` (without its history) is added as a cause of this error by `add_cause`.
+    ///
+    /// This is useful, for example, to keep a secondary error that occurred while handling this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// # fn main() {
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// let mut e = track!(Failed.cause("foo"));
+    /// let cleanup_error = track!(Failed.cause("bar"), "cleanup");
+    /// e.absorb(cleanup_error);
+    ///
+    /// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+    /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+    /// Failed
+    /// CAUSES:
+    ///   [0] foo
+    ///   [1] Failed (cause; bar)
+    /// HISTORY:
+    ///   [0] at src/error.rs:8:13
+    ///   [1] at src/error.rs:9:21 -- cleanup
+    /// "#);
+    /// # }
+    /// ```
+    pub fn absorb<K2>(&mut self, mut other: TrackableError<K2>)
+    where
+        K2: ErrorKind + Send + Sync + 'static,
+    {
+        let history = mem::take(&mut other.history);
+        self.history.append(history);
+        self.add_cause(other);
+    }
+
+    /// Returns the primary cause of this error as a trait object.
+    ///
+    /// Unlike [`concrete_cause`](#method.concrete_cause), this does not require knowing
+    /// the type of the cause, and is useful to format it or to walk its `source()` chain generically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause(std::fmt::Error);
+    /// assert_eq!(
+    ///     e.cause_ref().map(ToString::to_string),
+    ///     Some("an error occurred when formatting an argument".to_string())
+    /// );
+    /// assert!(Failed.error().cause_ref().is_none());
+    /// ```
+    pub fn cause_ref(&self) -> Option<&(dyn Error + Send + Sync + 'static)> {
+        self.cause.as_ref().map(|c| &*c.inner().error)
+    }
+
+    /// Returns an iterator over the causes of this error.
+    ///
+    /// The primary cause (if any) comes first, followed by the ones added by `add_cause`.
+    pub fn causes(&self) -> impl Iterator<Item = &(dyn Error + Send + Sync + 'static)> {
+        self.cause
+            .iter()
+            .chain(self.additional_causes.iter())
+            .map(|c| &*c.inner().error)
+    }
+
+    /// Tries to return the cause of this error as a value of `T` type.
+    ///
+    /// All the causes of this error are searched in the order of `causes`.
+    /// If none of them is an `T` value, this method will return `None`.
+    #[inline]
+    pub fn concrete_cause<T>(&self) -> Option<&T>
+    where
+        T: Error + 'static,
+    {
+        self.causes().filter_map(|c| c.downcast_ref()).next()
+    }
+}
+impl TrackableError<BoxErrorKind> {
+    /// Returns a reference to the original kind of this error if it is of type `K`,
+    /// otherwise `None`.
+    ///
+    /// This is the reverse operation of [`into_boxed_kind`](#method.into_boxed_kind).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failed};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum MyErrorKind {
+    ///     Foo,
+    /// }
+    /// impl ErrorKind for MyErrorKind {}
+    ///
+    /// let e = MyErrorKind::Foo.cause("something wrong").into_boxed_kind();
+    /// assert_eq!(e.downcast_kind::<MyErrorKind>(), Some(&MyErrorKind::Foo));
+    /// assert_eq!(e.downcast_kind::<Failed>(), None);
+    /// ```
+    pub fn downcast_kind<K: ErrorKind + 'static>(&self) -> Option<&K> {
+        (*self.kind).downcast_ref()
+    }
+}
+impl<K: ErrorKind + PartialEq> TrackableError<K> {
+    /// Returns `true` if this error and `other` have the same kind and causes, otherwise `false`.
+    ///
+    /// Causes are compared by their string representations.
+    /// The histories and the contexts of the errors are ignored.
+    ///
+    /// This is mainly intended for asserting errors in tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e0 = Failed.cause("something wrong");
+    /// let e1 = track!(Failed.cause("something wrong"));
+    /// assert!(e0.same_kind_and_cause(&e1));
+    /// assert!(!e0.same_kind_and_cause(&Failed.cause("another")));
+    /// # }
+    /// ```
+    pub fn same_kind_and_cause(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.causes().count() == other.causes().count()
+            && self
+                .causes()
+                .zip(other.causes())
+                .all(|(a, b)| a.to_string() == b.to_string())
+    }
+}
+impl<K: ErrorKind> From<K> for TrackableError<K> {
+    #[inline]
+    fn from(kind: K) -> Self {
+        Self::from_kind(kind)
+    }
+}
+impl<K: ErrorKind + Default> Default for TrackableError<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::from_kind(K::default())
+    }
+}
+impl<K: ErrorKind> TrackableError<K> {
+    /// Returns a wrapper that displays this error with ANSI colors.
+    ///
+    /// The kind, the section headers (e.g., `HISTORY:`) and the messages of the history entries are colored.
+    /// If the `NO_COLOR` environment variable is set to a non-empty value,
+    /// the wrapper displays this error in the same way as the plain `Display` implementation.
+    ///
+    /// This method is available only if the `color` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause("something wrong");
+    /// println!("{}", e.display_colored());
+    /// ```
+    #[cfg(feature = "color")]
+    pub fn display_colored(&self) -> DisplayColored<'_, K> {
+        DisplayColored(self)
+    }
+
+    /// Returns an object that displays this error with the given layout options.
+    ///
+    /// This is useful when embedding an error into a larger report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::DisplayOptions;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause("something wrong");
+    /// let e = e.with_context("key", "value");
+    /// let options = DisplayOptions::new()
+    ///     .with_indent("    ")
+    ///     .with_header("Trace:")
+    ///     .with_numbering(false);
+    /// assert_eq!(format!("\n{}", e.display_with(options)), r#"
+    /// Failed (cause; something wrong)
+    /// CONTEXT:
+    ///     key: value
+    /// Trace:
+    /// "#);
+    /// ```
+    pub fn display_with(&self, options: DisplayOptions) -> DisplayWith<'_, K> {
+        DisplayWith(self, options)
+    }
+
+    /// Returns an object that serializes this error with the cause messages limited to `max_len` bytes.
+    ///
+    /// A longer message is truncated at a character boundary not exceeding `max_len`,
+    /// and a suffix like `… (truncated 100 bytes)` is appended to it.
+    /// The other fields are serialized in the same way as the error itself.
+    ///
+    /// This is available only if the `serialize` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate serde_json;
+    /// # extern crate trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// # fn main() {
+    /// let e = Failed.cause("something wrong");
+    /// let json = serde_json::to_value(e.serialize_with_max_cause_len(5)).unwrap();
+    /// assert_eq!(json["cause"]["message"], "somet… (truncated 10 bytes)");
+    /// # }
+    /// ```
+    #[cfg(feature = "serialize")]
+    pub fn serialize_with_max_cause_len(&self, max_len: usize) -> SerializeWithMaxCauseLen<'_, K> {
+        SerializeWithMaxCauseLen(self, max_len)
+    }
+
+    /// Writes the `Display` form of this error to `writer`.
+    ///
+    /// The output is streamed to `writer` without building an intermediate string,
+    /// so this is suitable for writing errors that have large histories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # std::env::remove_var("RUST_BACKTRACE");
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.cause("something wrong");
+    /// let mut buf = Vec::new();
+    /// e.write_to(&mut buf).unwrap();
+    /// assert_eq!(buf, b"Failed (cause; something wrong)\nHISTORY:\n");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "{}", self)
+    }
+
+    /// Returns an object that displays this error with its history entries from the most recent one.
+    ///
+    /// This is equivalent to `self.display_with(DisplayOptions::new().with_reversed(true))`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = track!(Failed.error(), "first");
+    /// let e = track!(e, "second");
+    /// # if cfg!(feature = "disable_tracking") { return; }
+    /// let s = e.display_reversed().to_string();
+    /// assert!(s.contains("[0] at "));
+    /// assert!(s.find("second").unwrap() < s.find("first").unwrap());
+    /// # }
+    /// ```
+    pub fn display_reversed(&self) -> DisplayWith<'_, K> {
+        self.display_with(DisplayOptions::new().with_reversed(true))
+    }
+
+    /// Returns an object that displays this error in a compact single line.
+    ///
+    /// The line consists of the alternate form of `Display` (i.e., `{:#}`) followed by
+    /// the locations in the history joined by ` -> ` (e.g., `[src/a.rs:1:5 -> src/b.rs:2:9]`).
+    /// The messages of the history entries are omitted,
+    /// and line breaks in the causes are replaced with spaces.
+    ///
+    /// This is useful for metrics labels and short log lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{Location, Trackable};
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut e = Failed.cause("something wrong");
+    /// e.history_mut().unwrap().add(Location::new("a", "src/a.rs", 1, "foo"));
+    /// e.history_mut().unwrap().add(Location::new("b", "src/b.rs", 2, "").with_column(9));
+    /// assert_eq!(
+    ///     e.compact().to_string(),
+    ///     "Failed (cause; something wrong) [src/a.rs:1 -> src/b.rs:2:9]"
+    /// );
+    /// assert_eq!(Failed.error().compact().to_string(), "Failed");
+    /// ```
+    pub fn compact(&self) -> DisplayCompact<'_, K> {
+        DisplayCompact(self)
+    }
+
+    /// Returns a single-line [logfmt](https://brandur.org/logfmt) representation of this error.
+    ///
+    /// The output consists of the following fields:
+    /// - `kind`: the `Display` form of the kind of this error
+    /// - `code`: the code of the kind (omitted if it is `None`)
+    /// - `cause`: the `{:#}` form of each cause (repeated for additional causes)
+    /// - `history_len`: the number of the entries in the history
+    /// - `last_location`: the most recent location in the history (omitted if the history is empty)
+    ///
+    /// String values are quoted, and quotes, backslashes and line breaks in them are escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{Location, Trackable};
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut e = Failed.cause("something \"wrong\"\nhere");
+    /// e.history_mut().unwrap().add(Location::new("foo", "src/x.rs", 27, ""));
+    /// assert_eq!(
+    ///     e.to_logfmt(),
+    ///     r#"kind="Failed" cause="something \"wrong\"\nhere" history_len=1 last_location="src/x.rs:27""#
+    /// );
+    /// ```
+    pub fn to_logfmt(&self) -> String {
+        let mut s = String::from("kind=");
+        push_logfmt_value(&mut s, &ErrorKindDisplay(&self.kind).to_string());
+        if let Some(code) = self.kind.code() {
+            s.push_str(" code=");
+            push_logfmt_value(&mut s, code);
+        }
+        for e in self.causes() {
+            s.push_str(" cause=");
+            push_logfmt_value(&mut s, &format!("{:#}", e));
+        }
+        s.push_str(&format!(" history_len={}", self.history.len()));
+        if let Some(l) = self.history.last() {
+            let mut location = format!("{}:{}", l.file(), l.line());
+            if l.column() != 0 {
+                location.push_str(&format!(":{}", l.column()));
+            }
+            s.push_str(" last_location=");
+            push_logfmt_value(&mut s, &location);
+        }
+        s
+    }
+
+    fn fmt_styled(&self, f: &mut fmt::Formatter, style: Style) -> fmt::Result {
+        self.fmt_with_options(f, style, &DisplayOptions::default())
+    }
+
+    fn fmt_with_options(
+        &self,
+        f: &mut fmt::Formatter,
+        style: Style,
+        options: &DisplayOptions,
+    ) -> fmt::Result {
+        style.begin(f, Style::KIND)?;
+        match self.kind.user_message() {
+            Some(ref m) if f.alternate() => f.write_str(m)?,
+            _ => self.kind.display(f)?,
+        }
+        style.end(f)?;
+        if let Some(code) = self.kind.code() {
+            write!(f, " [{}]", code)?;
+        }
+        let severity = self.kind.severity();
+        if severity != Severity::Error {
+            write!(f, " (severity; {})", severity)?;
+        }
+        if f.alternate() {
+            for e in self.causes() {
+                write!(f, " (cause; {:#})", e)?;
+            }
+            if let Some(suggestion) = self.kind.suggestion() {
+                write!(f, "\nsuggestion: {}", suggestion)?;
+            }
+            return Ok(());
+        }
+        if self.additional_causes.is_empty() {
+            if let Some(ref e) = self.cause {
+                write!(f, " (cause; {})", e.inner())?;
+                let mut sources = iter::successors(e.inner().error.source(), |&e| e.source())
+                    .filter(|_| !e.inner().chain_in_history);
+                for e in sources.by_ref().take(MAX_SOURCE_DEPTH) {
+                    write!(f, "\n{}caused by: {:#}", options.indent(), e)?;
+                }
+                if sources.next().is_some() {
+                    write!(f, "\n{}... (cause chain truncated)", options.indent())?;
+                }
+            }
+        } else {
+            writeln!(f)?;
+            style.paint(f, Style::HEADER, "CAUSES:")?;
+            for (i, e) in self.causes().enumerate() {
+                write!(f, "\n{}[{}] {:#}", options.indent(), i, e)?;
+            }
+        }
+        if !self.context.is_empty() {
+            writeln!(f)?;
+            style.paint(f, Style::HEADER, "CONTEXT:")?;
+            for (k, v) in self.context() {
+                write!(f, "\n{}{}: {}", options.indent(), k, v)?;
+            }
+        }
+        writeln!(f)?;
+        self.history.fmt_locations(f, style, options)?;
+        #[cfg(feature = "backtrace")]
+        {
+            if let Some(ref b) = self.backtrace {
+                style.paint(f, Style::HEADER, "BACKTRACE:")?;
+                write!(f, "\n{}", b)?;
+            }
+        }
+        Ok(())
+    }
+}
+impl<K: ErrorKind> fmt::Display for TrackableError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_styled(f, Style::default())
+    }
+}
+
+struct ErrorKindDisplay<'a, K: 'a>(&'a K);
+impl<'a, K: ErrorKind> fmt::Display for ErrorKindDisplay<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.display(f)
+    }
+}
+
+fn push_logfmt_value(buf: &mut String, value: &str) {
+    buf.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            _ => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// A builder of `TrackableError`.
+///
+/// This is created by [`TrackableError::builder`](struct.TrackableError.html#method.builder).
+#[derive(Debug)]
+pub struct TrackableErrorBuilder<K>(TrackableError<K>);
+impl<K: ErrorKind> TrackableErrorBuilder<K> {
+    /// Adds a cause to the error.
+    ///
+    /// The first one becomes the primary cause (see [`TrackableError::add_cause`]).
+    ///
+    /// [`TrackableError::add_cause`]: struct.TrackableError.html#method.add_cause
+    pub fn cause<E>(mut self, cause: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        self.0.add_cause(cause);
+        self
+    }
+
+    /// Attaches a key-value pair to the error as a context
+    /// (see [`TrackableError::with_context`]).
+    ///
+    /// [`TrackableError::with_context`]: struct.TrackableError.html#method.with_context
+    pub fn context<V>(mut self, key: &'static str, value: V) -> Self
+    where
+        V: fmt::Display,
+    {
+        self.0 = self.0.with_context(key, value);
+        self
+    }
+
+    /// Adds the location of the caller into the history of the error.
+    #[track_caller]
+    pub fn track_here(mut self) -> Self {
+        self.0.track_here();
+        self
+    }
+
+    /// Builds the error.
+    pub fn build(self) -> TrackableError<K> {
+        self.0
+    }
+}
+
+/// A read-only `TrackableError` that can be shared cheaply.
+///
+/// This is created by [`TrackableError::freeze`](struct.TrackableError.html#method.freeze),
+/// and is useful to report an error to multiple destinations.
+/// Cloning a `FrozenError` does not copy the error (including its history)
+/// but only increments a reference count.
+///
+/// The history of a frozen error cannot be modified:
+/// its `history_mut()` always returns `None`, so [track!](../macro.track.html) is a no-op for it.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let e = track!(Failed.cause("something wrong")).freeze();
+/// # if cfg!(feature = "disable_tracking") { return; }
+/// let sinks = vec![e.clone(), e.clone()];
+/// assert!(sinks.iter().all(|s| s.history_len() == 1));
+///
+/// let e = track!(e); // no-op
+/// assert_eq!(e.history_len(), 1);
+/// assert_eq!(e.kind(), &Failed);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FrozenError<K>(Arc<TrackableError<K>>);
+impl<K> FrozenError<K> {
+    /// Returns `true` if both frozen errors share the same error, otherwise `false`.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+impl<K: Clone> FrozenError<K> {
+    /// Converts this into a mutable `TrackableError`.
+    ///
+    /// The error is copied only if it is shared by other frozen errors.
+    pub fn thaw(self) -> TrackableError<K> {
+        Arc::try_unwrap(self.0).unwrap_or_else(|e| (*e).clone())
+    }
+}
+impl<K> Clone for FrozenError<K> {
+    fn clone(&self) -> Self {
+        FrozenError(Arc::clone(&self.0))
+    }
+}
+impl<K> ops::Deref for FrozenError<K> {
+    type Target = TrackableError<K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<K: ErrorKind> fmt::Display for FrozenError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl<K: ErrorKind> Error for FrozenError<K> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+impl<K> Trackable for FrozenError<K> {
+    type Event = Location;
+
+    #[inline]
+    fn history(&self) -> Option<&History> {
+        Some(&self.0.history)
+    }
+
+    #[inline]
+    fn history_mut(&mut self) -> Option<&mut History> {
+        None
+    }
+}
+
+/// A wrapper that displays a `TrackableError` with custom layout options.
+///
+/// This is created by [`TrackableError::display_with`](struct.TrackableError.html#method.display_with).
+#[derive(Debug)]
+pub struct DisplayWith<'a, K: 'a>(&'a TrackableError<K>, DisplayOptions);
+impl<'a, K: ErrorKind> fmt::Display for DisplayWith<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_with_options(f, Style::default(), &self.1)
+    }
+}
+
+/// A wrapper that displays a `TrackableError` in a compact single line.
+///
+/// This is created by [`TrackableError::compact`](struct.TrackableError.html#method.compact).
+#[derive(Debug)]
+pub struct DisplayCompact<'a, K: 'a>(&'a TrackableError<K>);
+impl<'a, K: ErrorKind> fmt::Display for DisplayCompact<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let head = format!("{:#}", self.0);
+        f.write_str(&head.replace('\n', " "))?;
+        if self.0.history.is_empty() {
+            return Ok(());
+        }
+        f.write_str(" [")?;
+        for (i, l) in self.0.history.events().iter().enumerate() {
+            if i != 0 {
+                f.write_str(" -> ")?;
+            }
+            write!(f, "{}:{}", l.file(), l.line())?;
+            if l.column() != 0 {
+                write!(f, ":{}", l.column())?;
+            }
+        }
+        f.write_str("]")
+    }
+}
+
+/// A wrapper that displays a `TrackableError` with ANSI colors.
+///
+/// This is created by [`TrackableError::display_colored`](struct.TrackableError.html#method.display_colored).
+#[cfg(feature = "color")]
+#[derive(Debug)]
+pub struct DisplayColored<'a, K: 'a>(&'a TrackableError<K>);
+#[cfg(feature = "color")]
+impl<'a, K: ErrorKind> fmt::Display for DisplayColored<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        if no_color {
+            self.0.fmt_styled(f, Style::default())
+        } else {
+            self.0.fmt_styled(f, Style::colored())
+        }
+    }
+}
+impl<K: ErrorKind> Error for TrackableError<K> {
+    fn description(&self) -> &str {
+        self.kind.description()
+    }
+    fn cause(&self) -> Option<&dyn Error> {
+        self.cause
+            .as_ref()
+            .map::<&dyn Error, _>(|e| &*e.inner().error)
+    }
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause
+            .as_ref()
+            .map::<&(dyn Error + 'static), _>(|e| &*e.inner().error)
+    }
+}
+impl<K> Trackable for TrackableError<K> {
+    type Event = Location;
+
+    #[inline]
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    #[inline]
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
+}
+
+#[derive(Debug)]
+enum Cause {
+    Shared(Arc<CauseInner>),
+    Unshared(Box<CauseInner>),
+}
+impl Cause {
+    fn new<E>(error: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        Cause::Shared(Arc::new(CauseInner::new(error)))
+    }
+
+    fn new_unshared<E>(error: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        Cause::Unshared(Box::new(CauseInner::new(error)))
+    }
+
+    fn inner(&self) -> &CauseInner {
+        match self {
+            Cause::Shared(inner) => inner,
+            Cause::Unshared(inner) => inner,
+        }
+    }
+}
+impl Clone for Cause {
+    fn clone(&self) -> Self {
+        match self {
+            Cause::Shared(inner) => Cause::Shared(Arc::clone(inner)),
+            Cause::Unshared(inner) => Cause::Shared(Arc::new(CauseInner {
+                error: inner.to_string().into(),
+                type_name: inner.type_name.clone(),
+                alternate: false,
+                chain_in_history: false,
+            })),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CauseInner {
+    error: BoxError,
+    type_name: Option<Cow<'static, str>>,
+    alternate: bool,        // Whether `error` is displayed in the alternate form
+    chain_in_history: bool, // Whether the `source()` chain of `error` is recorded in the history
+}
+impl CauseInner {
+    fn new<E>(error: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        CauseInner {
+            error: error.into(),
+            type_name: Some(Cow::Borrowed(any::type_name::<E>())),
+            alternate: false,
+            chain_in_history: false,
+        }
+    }
+}
+impl fmt::Display for CauseInner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.alternate {
+            write!(f, "{:#}", self.error)
+        } else {
+            write!(f, "{}", self.error)
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Context(Vec<(Cow<'static, str>, String)>);
+impl Context {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(feature = "backtrace")]
+fn capture_backtrace() -> Option<Arc<Backtrace>> {
+    // Unit tests ignore `RUST_BACKTRACE` so that their results do not depend on the environment.
+    #[cfg(test)]
+    let enabled = test::CAPTURE_BACKTRACE.with(|c| c.get());
+    #[cfg(not(test))]
+    let enabled = match std::env::var_os("RUST_BACKTRACE") {
+        Some(v) => v != "0",
+        None => false,
+    };
+    if enabled {
+        Some(Arc::new(Backtrace::force_capture()))
+    } else {
+        None
+    }
+}
+
+/// A wrapper that serializes a `TrackableError` with the cause messages limited in length.
+///
+/// This is created by [`TrackableError::serialize_with_max_cause_len`](struct.TrackableError.html#method.serialize_with_max_cause_len).
+#[cfg(feature = "serialize")]
+#[derive(Debug)]
+pub struct SerializeWithMaxCauseLen<'a, K: 'a>(&'a TrackableError<K>, usize);
+
+#[cfg(feature = "serialize")]
+mod impl_serde {
+    use serde::de::{self, MapAccess, Visitor};
+    use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::borrow::Cow;
+    use std::fmt;
+    #[cfg(not(feature = "std"))]
+    use std::format;
+    use std::sync::Arc;
+
+    use super::{Cause, CauseInner, Context, ErrorKind, SerializeWithMaxCauseLen, TrackableError};
+    #[cfg(not(feature = "std"))]
+    use prelude::*;
+
+    impl<K> Serialize for TrackableError<K>
+    where
+        K: ErrorKind + Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize_error(self, None, serializer)
+        }
+    }
+
+    impl<'a, K> Serialize for SerializeWithMaxCauseLen<'a, K>
+    where
+        K: ErrorKind + Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize_error(self.0, Some(self.1), serializer)
+        }
+    }
+
+    fn serialize_error<K, S>(
+        e: &TrackableError<K>,
+        max_len: Option<usize>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        K: ErrorKind + Serialize,
+        S: Serializer,
+    {
+        let code = e.kind.code();
+        let len = 4
+            + code.is_some() as usize
+            + !e.additional_causes.is_empty() as usize
+            + !e.context.is_empty() as usize
+            + e.version.is_some() as usize;
+        let mut s = serializer.serialize_struct("TrackableError", len)?;
+        s.serialize_field("kind", &e.kind)?;
+        if let Some(code) = code {
+            s.serialize_field("code", code)?;
+        }
+        s.serialize_field("cause", &e.cause.as_ref().map(|c| CauseWith(c, max_len)))?;
+        if !e.additional_causes.is_empty() {
+            s.serialize_field(
+                "additional_causes",
+                &CausesWith(&e.additional_causes, max_len),
+            )?;
+        }
+        if !e.context.is_empty() {
+            s.serialize_field("context", &e.context)?;
+        }
+        if let Some(ref version) = e.version {
+            s.serialize_field("version", version)?;
+        }
+        s.serialize_field("history_len", &e.history.len())?;
+        s.serialize_field("history", &e.history)?;
+        s.end()
+    }
+
+    struct CausesWith<'a>(&'a [Cause], Option<usize>);
+    impl<'a> Serialize for CausesWith<'a> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+            for cause in self.0 {
+                seq.serialize_element(&CauseWith(cause, self.1))?;
+            }
+            seq.end()
+        }
+    }
+
+    struct CollectStr<'a, T: 'a>(&'a T);
+    impl<'a, T: fmt::Display> Serialize for CollectStr<'a, T> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_str(self.0)
+        }
+    }
+
+    impl Serialize for Cause {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            CauseWith(self, None).serialize(serializer)
+        }
+    }
+
+    struct CauseWith<'a>(&'a Cause, Option<usize>);
+    impl<'a> Serialize for CauseWith<'a> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let inner = self.0.inner();
+            let len = if inner.type_name.is_some() { 2 } else { 1 };
+            let mut map = serializer.serialize_map(Some(len))?;
+            if let Some(ref type_name) = inner.type_name {
+                map.serialize_entry("type", type_name)?;
+            }
+            match self.1 {
+                None => map.serialize_entry("message", &CollectStr(inner))?,
+                Some(max_len) => {
+                    let mut message = inner.to_string();
+                    if message.len() > max_len {
+                        let mut end = max_len;
+                        while !message.is_char_boundary(end) {
+                            end -= 1;
+                        }
+                        let truncated = message.len() - end;
+                        message.truncate(end);
+                        message += &format!("… (truncated {} bytes)", truncated);
+                    }
+                    map.serialize_entry("message", &message)?;
+                }
+            }
+            map.end()
+        }
+    }
+    impl<'de> Deserialize<'de> for Cause {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(CauseVisitor)
+        }
+    }
+
+    struct CauseVisitor;
+    impl<'de> Visitor<'de> for CauseVisitor {
+        type Value = Cause;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a string or a map which has `message` and `type` fields")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(Cause::Shared(Arc::new(CauseInner {
+                error: v.into(),
+                type_name: None,
+                alternate: false,
+                chain_in_history: false,
+            })))
+        }
+
+        fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut message = None;
+            let mut type_name = None;
+            while let Some(k) = access.next_key::<String>()? {
+                match k.as_str() {
+                    "message" => message = Some(access.next_value::<String>()?),
+                    "type" => type_name = Some(access.next_value::<String>()?),
+                    _ => {
+                        access.next_value::<de::IgnoredAny>()?;
+                    }
+                }
+            }
+            let message = message.ok_or_else(|| de::Error::missing_field("message"))?;
+            Ok(Cause::Shared(Arc::new(CauseInner {
+                error: message.into(),
+                type_name: type_name.map(Cow::Owned),
+                alternate: false,
+                chain_in_history: false,
+            })))
+        }
+    }
+
+    impl Serialize for Context {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (k, v) in &self.0 {
+                map.serialize_entry(k, v)?;
+            }
+            map.end()
+        }
+    }
+    impl<'de> Deserialize<'de> for Context {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(ContextVisitor)
+        }
+    }
+
+    struct ContextVisitor;
+    impl<'de> Visitor<'de> for ContextVisitor {
+        type Value = Context;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a map of strings")
+        }
+
+        fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut context = Vec::new();
+            while let Some((k, v)) = access.next_entry::<String, String>()? {
+                context.push((Cow::Owned(k), v));
+            }
+            Ok(Context(context))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std;
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    #[cfg(not(feature = "disable_tracking"))]
+    use test::strip_thread;
+
+    #[cfg(feature = "backtrace")]
+    thread_local! {
+        pub static CAPTURE_BACKTRACE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn it_works() {
+        #[derive(Debug, TrackableError)]
+        #[trackable(error_kind = "MyErrorKind")]
+        struct MyError(TrackableError<MyErrorKind>);
+        impl From<std::io::Error> for MyError {
+            fn from(f: std::io::Error) -> Self {
+                // Any I/O errors are considered critical
+                MyErrorKind::Critical.cause(f).into()
+            }
+        }
+
+        #[allow(dead_code)]
+        #[derive(Debug, PartialEq, Eq)]
+        enum MyErrorKind {
+            Critical,
+            NonCritical,
+        }
+        impl ErrorKind for MyErrorKind {}
+
+        // Tracks an error
+        let error: MyError = MyErrorKind::Critical.cause("something wrong").into();
+        let error = track!(error);
+        let error = track!(error, "I passed here");
+        assert_eq!(
+            strip_thread(&format!("\nError: {}", error).replace('\\', "/")),
+            r#"
+Error: Critical (cause; something wrong)
+HISTORY:
+  [0] at src/error.rs:3069:21
+  [1] at src/error.rs:3070:21 -- I passed here
+"#
+        );
+
+        // Tries to execute I/O operation
+        let result = (|| -> Result<_, MyError> {
+            let f =
+                track!(std::fs::File::open("/path/to/non_existent_file").map_err(MyError::from,))?;
+            Ok(f)
+        })();
+        let error = result.err().unwrap();
+        let cause = error.concrete_cause::<std::io::Error>().unwrap();
+        assert_eq!(cause.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn failure_from_io_error_works() {
+        let e = Failure::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        let cause = e.concrete_cause::<io::Error>().unwrap();
+        assert_eq!(cause.kind(), io::ErrorKind::NotFound);
+        assert_eq!(e.to_string(), "Failed (cause; no such file)\nHISTORY:\n");
+
+        let e = track!(e);
+        let e: Failure = Failed.takes_over(e).into();
+        let cause = e.concrete_cause::<io::Error>().unwrap();
+        assert_eq!(cause.kind(), io::ErrorKind::NotFound);
+        assert_eq!(e.history().unwrap().len(), 1);
+
+        // `from_error` is unchanged
+        let e = Failure::from_error(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        let cause = e.concrete_cause::<io::Error>().unwrap();
+        assert_eq!(cause.kind(), io::ErrorKind::PermissionDenied);
+        assert!(Failure::from_error("foo")
+            .concrete_cause::<io::Error>()
+            .is_none());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn multiple_causes_works() {
+        let mut e = Failed.error();
+        assert_eq!(e.causes().count(), 0);
+        assert_eq!(e.to_string(), "Failed\nHISTORY:\n");
+
+        e.add_cause("foo");
+        assert_eq!(e.to_string(), "Failed (cause; foo)\nHISTORY:\n");
+
+        e.add_cause(std::io::Error::new(std::io::ErrorKind::NotFound, "bar"));
+        e.add_cause("baz");
+        assert_eq!(
+            e.to_string(),
+            "Failed\nCAUSES:\n  [0] foo\n  [1] bar\n  [2] baz\nHISTORY:\n"
+        );
+
+        let cause = e.concrete_cause::<std::io::Error>().unwrap();
+        assert_eq!(cause.kind(), std::io::ErrorKind::NotFound);
+
+        let e = Failed.takes_over(e);
+        assert_eq!(e.causes().count(), 3);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn severity_works() {
+        #[derive(Debug)]
+        enum MyErrorKind {
+            Fatal,
+            Ignorable,
+        }
+        impl ErrorKind for MyErrorKind {
+            fn severity(&self) -> Severity {
+                match *self {
+                    MyErrorKind::Fatal => Severity::Critical,
+                    MyErrorKind::Ignorable => Severity::Debug,
+                }
+            }
+        }
+
+        let e = Failed.cause("foo");
+        assert_eq!(e.severity(), Severity::Error);
+        assert_eq!(e.to_string(), "Failed (cause; foo)\nHISTORY:\n");
+
+        let e = MyErrorKind::Fatal.cause("foo");
+        assert_eq!(e.severity(), Severity::Critical);
+        assert_eq!(
+            e.to_string(),
+            "Fatal (severity; Critical) (cause; foo)\nHISTORY:\n"
+        );
+
+        let e = MyErrorKind::Ignorable.takes_over(e);
+        assert_eq!(e.severity(), Severity::Debug);
+        assert!(Severity::Debug < Severity::Error);
+        assert!(Severity::Error < Severity::Critical);
+    }
+
+    #[test]
+    fn alternate_display_works() {
+        let e = track!(Failed.cause("foo"));
+        let e = track!(e, "hello");
+        assert_eq!(format!("{:#}", e), "Failed (cause; foo)");
+        assert!(!format!("{:#}", e).contains('\n'));
+        assert!(!format!("{:#}", e).contains("HISTORY:"));
+        assert!(format!("{}", e).contains("HISTORY:"));
+
+        let e: Failure = track!(Failed.cause(e)).into();
+        assert_eq!(format!("{:#}", e), "Failed (cause; Failed (cause; foo))");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn absorb_works() {
+        #[derive(Debug)]
+        struct Cleanup;
+        impl ErrorKind for Cleanup {}
+
+        let e = track!(Failed.cause("foo"), "primary 1");
+        let mut e = track!(e, "primary 2");
+
+        let other = track!(Cleanup.cause("bar"), "secondary 1");
+        let other = track!(other, "secondary 2");
+        e.absorb(other);
+        let e = track!(e, "after");
+
+        let messages = e
+            .history()
+            .unwrap()
+            .events()
+            .iter()
+            .map(|l| l.message())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "primary 1",
+                "primary 2",
+                "secondary 1",
+                "secondary 2",
+                "after"
+            ]
+        );
+
+        let s = e.to_string();
+        let positions = messages
+            .iter()
+            .map(|m| s.find(m).unwrap())
+            .collect::<Vec<_>>();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        assert!(s.contains("CAUSES:\n  [0] foo\n  [1] Cleanup (cause; bar)\n"));
+
+        let cause = e.concrete_cause::<TrackableError<Cleanup>>().unwrap();
+        assert!(cause.history().unwrap().is_empty());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn failure_box_error_conversion_works() {
+        let e: Failure = track!(Failed.cause("foo")).into();
+        let e = track!(e, "hello");
+        let expected = e.to_string();
+
+        let boxed: BoxError = e.into();
+        assert_eq!(boxed.to_string(), expected);
+
+        let e = Failure::from_boxed(boxed);
+        assert_eq!(e.to_string(), expected);
+        assert_eq!(e.history().unwrap().len(), 2);
+
+        let e = Failure::from_boxed(Box::new(std::fmt::Error));
+        assert!(e.concrete_cause::<std::fmt::Error>().is_some());
+        assert_eq!(
+            e.to_string(),
+            "Failed (cause; an error occurred when formatting an argument)\nHISTORY:\n"
+        );
+    }
+
+    #[test]
+    fn same_kind_and_cause_works() {
+        let e0 = Failed.cause("foo");
+        let e1 = track!(track!(Failed.cause("foo")), "bar");
+        assert!(e0.same_kind_and_cause(&e1));
+        assert!(e1.same_kind_and_cause(&e0));
+
+        assert!(!e0.same_kind_and_cause(&Failed.cause("bar")));
+        assert!(!e0.same_kind_and_cause(&Failed.error()));
+
+        let e2: TrackableError<String> = "Foo".to_string().cause("foo");
+        let e3: TrackableError<String> = "Bar".to_string().cause("foo");
+        assert!(!e2.same_kind_and_cause(&e3));
+
+        let mut e4 = Failed.cause("foo");
+        e4.add_cause("bar");
+        assert!(!e0.same_kind_and_cause(&e4));
+        assert!(!e4.same_kind_and_cause(&e0));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn display_with_works() {
+        let mut e = Failed.cause("foo");
+        e.add_cause("bar");
+        {
+            let history = e.history_mut().unwrap();
+            history.add(Location::new("foo", "src/foo.rs", 10, "hello"));
+            history.add(Location::new("foo", "src/foo.rs", 20, ""));
+        }
+
+        let options = DisplayOptions::new()
+            .with_indent("    ")
+            .with_header("== trace ==");
+        assert_eq!(
+            strip_thread(&format!("\n{}", e.display_with(options))),
+            r#"
+Failed
+CAUSES:
+    [0] foo
+    [1] bar
+== trace ==
+    [0] at src/foo.rs:10 -- hello
+    [1] at src/foo.rs:20
+"#
+        );
+
+        let options = DisplayOptions::new().with_numbering(false);
+        assert!(e
+            .display_with(options)
+            .to_string()
+            .ends_with("\n  at src/foo.rs:20\n"));
+
+        assert_eq!(
+            e.display_with(DisplayOptions::default()).to_string(),
+            e.to_string()
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn display_colored_works() {
+        let mut e = track!(Failed.cause("foo"), "hello");
+        e.add_cause("bar");
+        let plain = e.to_string();
+
+        std::env::remove_var("NO_COLOR");
+        let colored = e.display_colored().to_string();
+        assert!(colored.contains("\x1b["));
+        assert!(colored.starts_with("\x1b[1;31mFailed\x1b[0m\n"));
+        assert!(colored.contains("\x1b[1mHISTORY:\x1b[0m\n"));
+        assert!(colored.contains("\x1b[36m-- hello\x1b[0m\n"));
+        assert_eq!(
+            colored
+                .replace("\x1b[1;31m", "")
+                .replace("\x1b[1m", "")
+                .replace("\x1b[36m", "")
+                .replace("\x1b[0m", ""),
+            plain
+        );
+
+        std::env::set_var("NO_COLOR", "1");
+        let no_color = e.display_colored().to_string();
+        std::env::remove_var("NO_COLOR");
+        assert!(!no_color.contains('\x1b'));
+        assert_eq!(no_color, plain);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn cause_with_works() {
+        let mut calls = 0;
+        let value: Result<u32, _> = Ok(10);
+        let value = value.or_else(|()| {
+            Failed.fail_with(|| {
+                calls += 1;
+                "foo"
+            })
+        });
+        assert_eq!(value.ok(), Some(10));
+        assert_eq!(calls, 0);
+
+        let value: Result<u32, _> = Err(());
+        let value = value.or_else(|()| {
+            Failed.fail_with(|| {
+                calls += 1;
+                "foo"
+            })
+        });
+        assert_eq!(calls, 1);
+        assert_eq!(
+            value.err().unwrap().to_string(),
+            "Failed (cause; foo)\nHISTORY:\n"
+        );
+
+        let e = Failed.cause_with(|| {
+            calls += 1;
+            "bar"
+        });
+        assert_eq!(calls, 2);
+        assert_eq!(e.to_string(), "Failed (cause; bar)\nHISTORY:\n");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn failure_from_message_works() {
+        fn foo(id: u32) -> Result<(), Failure> {
+            if id == 0 {
+                return Err("something broke".into());
+            }
+            Err(format!("no such entry: {}", id).into())
+        }
+
+        let e = foo(0).unwrap_err();
+        assert_eq!(e.to_string(), "Failed (cause; something broke)\nHISTORY:\n");
+        let e = track!(foo(10)).unwrap_err();
+        assert!(e
+            .to_string()
+            .starts_with("Failed (cause; no such entry: 10)\nHISTORY:\n  [0] at "));
+        assert_eq!(e.source().unwrap().to_string(), "no such entry: 10");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn from_trackable_error_works() {
+        #[derive(Debug, TrackableError)]
+        #[trackable(error_kind = "Failed")]
+        struct MyError(TrackableError<Failed>);
+
+        let e: MyError = track!(Failed.cause("foo").into());
+        let e = track!(e, "bar");
+        let e = Failure::from_trackable_error(e);
+        assert_eq!(e.history().unwrap().len(), 2);
+        assert_eq!(e.history().unwrap().events()[1].message(), "bar");
+
+        let cause = e.concrete_cause::<MyError>().unwrap();
+        assert_eq!(cause.history().unwrap().len(), 0);
+        assert_eq!(cause.source().unwrap().to_string(), "foo");
+
+        let e = track!(Failure::from_trackable_error(e));
+        assert_eq!(e.history().unwrap().len(), 3);
+
+        // No (empty) history block of the cause is embedded in the header
+        let s = e.to_string();
+        assert!(s.starts_with("Failed (cause; Failed (cause; Failed (cause; foo)))\n"));
+        assert_eq!(s.matches("HISTORY:").count(), 1);
+        assert_eq!(
+            format!("{:#}", e),
+            "Failed (cause; Failed (cause; Failed (cause; foo)))"
+        );
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn anyhow_conversion_works() {
+        let e = track!(Failed.cause("foo"), "bar");
+        let s = e.to_string();
+
+        let e = anyhow::Error::from(e);
+        let debug = format!("{:?}", e);
+        assert!(debug.starts_with(&s));
+        assert!(debug.contains("HISTORY:\n  [0] at src/error.rs:"));
+        assert!(debug.contains(" -- bar"));
+        assert!(e.downcast_ref::<TrackableError<Failed>>().is_some());
+
+        let e = anyhow::Error::from(Failure::from(track!(Failed.cause("foo"))));
+        let e = Failure::from_anyhow(e);
+        assert_eq!(e.history().unwrap().len(), 1);
+
+        let e = Failure::from_anyhow(anyhow::Error::from(std::fmt::Error).context("baz"));
+        assert_eq!(
+            e.to_string(),
+            "Failed (cause; baz)\n  caused by: an error occurred when formatting an argument\nHISTORY:\n"
+        );
+        assert_eq!(e.history().unwrap().len(), 0);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_count_works() {
+        fn foo() -> Result<(), Failure> {
+            track_panic!(Failed)
+        }
+        fn bar() -> Result<(), Failure> {
+            track!(foo())
+        }
+        fn baz() -> Result<(), Failure> {
+            track!(bar())
+        }
+
+        let e = baz().err().unwrap();
+        assert_eq!(e.track_count(), 3);
+        assert_eq!(e.history_len(), 3);
+
+        let r: Result<(), Failure> = Ok(());
+        assert_eq!(r.history_len(), 0);
+        assert_eq!(baz().history_len(), 3);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn into_boxed_kind_works() {
+        #[derive(Debug)]
+        struct Timeout;
+        impl ErrorKind for Timeout {
+            fn severity(&self) -> Severity {
+                Severity::Warning
+            }
+            fn is_retriable(&self) -> bool {
+                true
+            }
+        }
+
+        let mut errors: Vec<TrackableError<BoxErrorKind>> = Vec::new();
+        errors.push(track!(Failed.cause("foo")).into_boxed_kind());
+        errors.push(Timeout.cause("bar").into_boxed_kind());
+        assert_eq!(errors[0].history().unwrap().len(), 1);
+        assert_eq!(errors[0].source().unwrap().to_string(), "foo");
+        assert!(errors[0]
+            .to_string()
+            .starts_with("Failed (cause; foo)\nHISTORY:\n  [0] at "));
+        assert_eq!(
+            errors[1].to_string(),
+            "Timeout (severity; Warning) (cause; bar)\nHISTORY:\n"
+        );
+        assert!(errors[1].is_retriable());
+        assert_eq!(errors[1].kind().description(), "An error");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn downcast_kind_works() {
+        #[derive(Debug, PartialEq)]
+        enum MyErrorKind {
+            Foo,
+            Bar(u32),
+        }
+        impl ErrorKind for MyErrorKind {}
+
+        let e = track!(MyErrorKind::Bar(10).cause("foo")).into_boxed_kind();
+        assert_eq!(
+            e.downcast_kind::<MyErrorKind>(),
+            Some(&MyErrorKind::Bar(10))
+        );
+        assert_eq!(e.downcast_kind::<Failed>(), None);
+        assert!(e.kind().is::<MyErrorKind>());
+        assert_eq!(e.history().unwrap().len(), 1);
+
+        let e = MyErrorKind::Foo.error().into_boxed_kind();
+        assert_eq!(e.downcast_kind::<MyErrorKind>(), Some(&MyErrorKind::Foo));
+        assert_eq!(e.downcast_kind::<BoxErrorKind>().map(|_| ()), None);
+
+        let e = Failed.error().into_boxed_kind();
+        assert_eq!(e.downcast_kind::<Failed>(), Some(&Failed));
+        assert_eq!(e.downcast_kind::<MyErrorKind>(), None);
+    }
+
+    #[test]
+    fn is_works() {
+        #[derive(Debug)]
+        struct Timeout;
+        impl ErrorKind for Timeout {}
+
+        let e = Timeout.cause("foo").into_boxed_kind();
+        assert!(e.is::<Timeout>());
+        assert!(!e.is::<Failed>());
+        assert!(!e.is::<BoxErrorKind>());
+
+        let e = Failed.error().into_boxed_kind();
+        assert!(e.is::<Failed>());
+        assert!(!e.is::<Timeout>());
+
+        let e = Timeout.error();
+        assert!(e.is::<Timeout>());
+        assert!(!e.is::<Failed>());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn wrap_works() {
+        #[derive(Debug, PartialEq)]
+        enum MyErrorKind {
+            Storage,
+        }
+        impl ErrorKind for MyErrorKind {}
+
+        let original: Failure = track!(Failed.cause("disk full").into());
+        let original_string = original.to_string();
+        let e = track!(MyErrorKind::Storage.wrap(original));
+        assert_eq!(*e.kind(), MyErrorKind::Storage);
+        assert_eq!(e.history().unwrap().len(), 1);
+
+        let source = e.source().unwrap();
+        let f = source.downcast_ref::<Failure>().unwrap();
+        assert_eq!(f.history().unwrap().len(), 1);
+        assert_eq!(source.to_string(), original_string);
+        assert_eq!(f.source().unwrap().to_string(), "disk full");
+        assert!(e.concrete_cause::<Failure>().is_some());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn take_cause_works() {
+        let mut e = track!(Failed.cause(std::fmt::Error));
+        let cause = e.take_cause().unwrap();
+        assert!(cause.downcast_ref::<std::fmt::Error>().is_some());
+        assert!(e.source().is_none());
+        assert_eq!(e.history().unwrap().len(), 1);
+
+        let mut e = Failed.cause("foo");
+        let cloned = e.clone();
+        assert!(e.take_cause().is_none());
+        assert_eq!(e.source().unwrap().to_string(), "foo");
+        assert_eq!(cloned.source().unwrap().to_string(), "foo");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn display_source_chain_works() {
+        #[derive(Debug)]
+        struct Chained(&'static str, Option<Box<Chained>>);
+        impl fmt::Display for Chained {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+        impl Error for Chained {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                self.1.as_ref().map(|e| &**e as _)
+            }
+        }
+
+        let e = Chained(
+            "outer",
+            Some(Box::new(Chained(
+                "middle",
+                Some(Box::new(Chained("inner", None))),
+            ))),
+        );
+        let e = Failed.cause(e).with_context("id", 1);
+        assert_eq!(
+            e.to_string(),
+            "Failed (cause; outer)\n  caused by: middle\n  caused by: inner\nCONTEXT:\n  id: 1\nHISTORY:\n"
+        );
+        assert_eq!(format!("{:#}", e), "Failed (cause; outer)");
+        let options = DisplayOptions::new().with_indent("    ");
+        assert!(e
+            .display_with(options)
+            .to_string()
+            .starts_with("Failed (cause; outer)\n    caused by: middle\n    caused by: inner\n"));
+
+        // Unchanged if the cause has no sources
+        let e = Failed.cause(Chained("foo", None));
+        assert_eq!(e.to_string(), "Failed (cause; foo)\nHISTORY:\n");
+
+        #[derive(Debug)]
+        struct Cyclic;
+        impl fmt::Display for Cyclic {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("cyclic")
+            }
+        }
+        impl Error for Cyclic {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(self)
+            }
+        }
+        let e = Failed.cause(Cyclic);
+        assert_eq!(
+            e.to_string().matches("caused by: cyclic").count(),
+            MAX_SOURCE_DEPTH
+        );
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn display_source_chain_truncation_works() {
+        #[derive(Debug)]
+        struct Deep(usize, Option<Box<Deep>>);
+        impl fmt::Display for Deep {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "level {}", self.0)
+            }
+        }
+        impl Error for Deep {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                self.1.as_ref().map(|e| &**e as _)
+            }
+        }
+        fn deep(depth: usize) -> Deep {
+            (1..=depth).fold(Deep(depth, None), |e, i| Deep(depth - i, Some(Box::new(e))))
+        }
+
+        // Exactly at the limit
+        let s = Failed.cause(deep(MAX_SOURCE_DEPTH)).to_string();
+        assert_eq!(s.matches("caused by: ").count(), MAX_SOURCE_DEPTH);
+        assert!(!s.contains("truncated"));
+
+        // Beyond the limit
+        let s = Failed.cause(deep(1000)).to_string();
+        assert_eq!(s.matches("caused by: ").count(), MAX_SOURCE_DEPTH);
+        assert!(s.contains(&format!(
+            "\n  caused by: level {}\n  ... (cause chain truncated)\nHISTORY:\n",
+            MAX_SOURCE_DEPTH
+        )));
+
+        // Cyclic
+        #[derive(Debug)]
+        struct Cyclic;
+        impl fmt::Display for Cyclic {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("cyclic")
+            }
+        }
+        impl Error for Cyclic {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(self)
+            }
+        }
+        let s = Failed.cause(Cyclic).to_string();
+        assert!(s.ends_with("caused by: cyclic\n  ... (cause chain truncated)\nHISTORY:\n"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn from_error_chained_works() {
+        #[derive(Debug)]
+        struct Chained(&'static str, Option<Box<Chained>>);
+        impl fmt::Display for Chained {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+        impl Error for Chained {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                self.1.as_ref().map(|e| &**e as _)
+            }
+        }
+
+        let e = Chained(
+            "outer",
+            Some(Box::new(Chained(
+                "middle",
+                Some(Box::new(Chained("inner", None))),
+            ))),
+        );
+        let e = track!(TrackableError::from_error_chained(Failed, e));
+        assert_eq!(e.source().unwrap().to_string(), "outer");
+        assert!(e.concrete_cause::<Chained>().is_some());
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].message(), "caused by: middle");
+        assert_eq!(events[1].message(), "caused by: inner");
+        assert_eq!(events[0].file(), file!());
+        assert_eq!(events[2].message(), "");
+
+        // The chain is shown only in the history
+        let s = e.to_string();
+        assert!(!s.contains("\n  caused by: "));
+        assert_eq!(s.matches("caused by: middle").count(), 1);
+        assert_eq!(s.matches("caused by: inner").count(), 1);
+
+        let e = TrackableError::from_error_chained(Failed, Chained("foo", None));
+        assert_eq!(e.track_count(), 0);
+
+        #[derive(Debug)]
+        struct Cyclic;
+        impl fmt::Display for Cyclic {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("cyclic")
+            }
+        }
+        impl Error for Cyclic {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(self)
+            }
+        }
+        let e = TrackableError::from_error_chained(Failed, Cyclic);
+        assert_eq!(e.track_count(), MAX_SOURCE_DEPTH);
+    }
+
+    #[test]
+    fn new_unshared_works() {
+        let e = track!(TrackableError::new_unshared(Failed, std::fmt::Error));
+        assert!(e.concrete_cause::<std::fmt::Error>().is_some());
+        assert_eq!(e.cause_type_name(), Some("core::fmt::Error"));
+
+        let mut cloned = e.clone();
+        assert!(cloned.concrete_cause::<std::fmt::Error>().is_none());
+        assert_eq!(cloned.cause_type_name(), Some("core::fmt::Error"));
+        assert_eq!(cloned.to_string(), e.to_string());
+
+        // The clone shares its (copied) cause with the clones of itself as usual
+        let cloned2 = cloned.clone();
+        assert!(cloned.take_cause().is_none());
+        drop(cloned2);
+        assert_eq!(
+            cloned.take_cause().unwrap().to_string(),
+            "an error occurred when formatting an argument"
+        );
+
+        // The cause of an unshared error can always be taken
+        let mut e = e;
+        let cause = e.take_cause().unwrap();
+        assert!(cause.downcast_ref::<std::fmt::Error>().is_some());
+    }
+
+    #[test]
+    fn is_retriable_works() {
+        #[derive(Debug)]
+        struct Unavailable;
+        impl ErrorKind for Unavailable {
+            fn is_retriable(&self) -> bool {
+                true
+            }
+        }
+
+        let e = Failed.cause("foo");
+        assert!(!e.is_retriable());
+
+        let e = Unavailable.takes_over(e);
+        assert!(e.is_retriable());
+
+        let e = track!(e);
+        assert!(e.is_retriable());
+
+        let e = Failed.takes_over(e);
+        assert!(!e.is_retriable());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn map_kind_works() {
+        #[derive(Debug, PartialEq, Eq)]
+        enum Kind0 {
+            Foo,
+            #[allow(dead_code)]
+            Bar,
+        }
+        impl ErrorKind for Kind0 {}
+
+        #[derive(Debug)]
+        struct Kind1(String);
+        impl ErrorKind for Kind1 {
+            fn description(&self) -> &str {
+                &self.0
+            }
+        }
+
+        let e = track!(Kind0::Foo.cause("something wrong"));
+        let e = track!(e, "hello");
+        let before = e.history().unwrap().events().to_vec();
+
+        let e = e.map_kind(|k| Kind1(format!("{:?}", k)));
+        assert_eq!(e.kind().0, "Foo");
+        assert_eq!(e.source().unwrap().to_string(), "something wrong");
+
+        let after = e.history().unwrap().events();
+        assert_eq!(after.len(), 2);
+        for (a, b) in before.iter().zip(after.iter()) {
+            assert_eq!(a.to_string(), b.to_string());
+        }
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn context_works() {
+        #[derive(Debug)]
+        struct Kind0;
+        impl ErrorKind for Kind0 {}
+
+        let e = Kind0
+            .cause("something wrong")
+            .with_context("request_id", 42)
+            .with_context("user_id", "alice");
+        let e = Failed.takes_over(e);
+        assert_eq!(
+            e.context().collect::<Vec<_>>(),
+            [("request_id", "42"), ("user_id", "alice")]
+        );
+        assert_eq!(
+            e.to_string(),
+            "Failed (cause; something wrong)\nCONTEXT:\n  request_id: 42\n  user_id: alice\nHISTORY:\n"
+        );
+    }
+
+    #[cfg(feature = "serialize")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn serialize_context_works() {
+        extern crate serde_json;
+
+        let e = Failed.error().with_context("request_id", 42);
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json["context"]["request_id"], "42");
+
+        let d: TrackableError<Failed> = serde_json::from_value(json).unwrap();
+        assert_eq!(d.context().collect::<Vec<_>>(), [("request_id", "42")]);
+        assert_eq!(d.to_string(), e.to_string());
+
+        let json = serde_json::to_string(&Failed.error()).unwrap();
+        assert!(!json.contains("context"));
+    }
+
+    #[test]
+    fn source_works() {
+        let e = Failed.cause(std::io::Error::new(std::io::ErrorKind::NotFound, "foo"));
+        let source = e.source().unwrap();
+        assert_eq!(source.to_string(), "foo");
+
+        let io_error = source.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io_error.kind(), std::io::ErrorKind::NotFound);
+
+        let e = Failure::from(track!(e));
+        assert!(e.source().unwrap().is::<std::io::Error>());
+        assert!(Failed.error().source().is_none());
+    }
+
+    #[cfg(feature = "backtrace")]
+    #[test]
+    fn backtrace_works() {
+        let e = Failed.cause("something wrong");
+        assert!(e.backtrace().is_none());
+        assert!(!e.to_string().contains("\nBACKTRACE:\n"));
+
+        CAPTURE_BACKTRACE.with(|c| c.set(true));
+        let e = Failed.cause("something wrong");
+        CAPTURE_BACKTRACE.with(|c| c.set(false));
+        assert!(e.backtrace().is_some());
+        assert!(e.to_string().contains("\nBACKTRACE:\n"));
+
+        let e = track!(Failed.takes_over(e));
+        assert!(e.backtrace().is_some());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn serialize_multiple_causes_works() {
+        extern crate serde_json;
+
+        let mut e = Failed.cause("foo");
+        e.add_cause("bar");
+        let e = track!(e);
+
+        let json = serde_json::to_string(&e).unwrap();
+        let d: TrackableError<Failed> = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.to_string(), e.to_string());
+
+        let e = Failed.cause("foo");
+        let json = serde_json::to_string(&e).unwrap();
+        assert!(!json.contains("additional_causes"));
+        let d: TrackableError<Failed> = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.to_string(), e.to_string());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn serialize_schema_works() {
+        extern crate serde_json;
+
+        let e = track!(Failed.cause("something wrong"));
+        let e = track!(e, "I passed here");
+
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json["kind"], serde_json::Value::Null);
+        assert_eq!(json["cause"]["type"], "&str");
+        assert_eq!(json["cause"]["message"], "something wrong");
+        let history = json["history"].as_array().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0]["file"], "src/error.rs");
+        assert!(history[0]["line"].is_u64());
+        assert_eq!(history[0]["message"], "");
+        assert_eq!(history[1]["message"], "I passed here");
+
+        let d: TrackableError<Failed> = serde_json::from_value(json).unwrap();
+        assert_eq!(d.to_string(), e.to_string());
+
+        let json = serde_json::to_value(Failed.error()).unwrap();
+        assert_eq!(json["cause"], serde_json::Value::Null);
+
+        let json = r#"{"kind":null,"cause":null,"history":[{"file":"a.rs","line":3}]}"#;
+        let d: TrackableError<Failed> = serde_json::from_str(json).unwrap();
+        assert_eq!(d.to_string(), "Failed\nHISTORY:\n  [0] at a.rs:3\n");
+
+        let json = r#"{"kind":null,"cause":"foo","history":[]}"#;
+        let d: TrackableError<Failed> = serde_json::from_str(json).unwrap();
+        assert_eq!(d.to_string(), "Failed (cause; foo)\nHISTORY:\n");
+        assert_eq!(d.cause_type_name(), None);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn serialize_history_len_works() {
+        extern crate serde_json;
+
+        let json = serde_json::to_value(Failed.error()).unwrap();
+        assert_eq!(json["history_len"], 0);
+
+        let mut e = track!(Failed.cause("something wrong"));
+        e.history_mut().unwrap().set_max_len(3);
+        for i in 0..4 {
+            e = track!(e, "i={}", i);
+        }
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json["history_len"], 3);
+        assert_eq!(json["history"].as_array().unwrap().len(), 3);
+
+        let d: TrackableError<Failed> = serde_json::from_value(json).unwrap();
+        assert_eq!(d.history().unwrap().len(), 3);
+        let json = serde_json::to_value(&d).unwrap();
+        assert_eq!(json["history_len"], 3);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn serialize_version_works() {
+        extern crate serde_json;
+
+        let json = serde_json::to_value(Failed.error()).unwrap();
+        assert!(json.get("version").is_none());
+
+        let mut e = track!(Failed.cause("something wrong"));
+        e.stamp_version("1.2.3+abcdef");
+        let e = track!(e);
+        assert_eq!(e.version(), Some("1.2.3+abcdef"));
+
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json["version"], "1.2.3+abcdef");
+
+        let d: TrackableError<Failed> = serde_json::from_value(json).unwrap();
+        assert_eq!(d.version(), Some("1.2.3+abcdef"));
+        assert_eq!(d.history().unwrap().len(), 2);
+        assert_eq!(
+            serde_json::to_value(&d).unwrap(),
+            serde_json::to_value(&e).unwrap()
+        );
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn compact_works() {
+        let mut e = Failed.cause("first line\nsecond line");
+        e.add_cause("bar");
+        let e = track!(e);
+        let e = track!(e, "with message");
+        let e = track!(e);
+        let line = line!() - 3;
+
+        let s = e.compact().to_string();
+        assert!(!s.contains('\n'));
+        assert_eq!(
+            s.replace('\\', "/"),
+            format!(
+                "Failed (cause; first line second line) (cause; bar) \
+                 [src/error.rs:{}:17 -> src/error.rs:{}:17 -> src/error.rs:{}:17]",
+                line,
+                line + 1,
+                line + 2
+            )
+        );
+        assert_eq!(Failed.error().compact().to_string(), "Failed");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn default_display_works() {
+        #[derive(Debug)]
+        enum Kind0 {
+            Foo,
+        }
+        impl ErrorKind for Kind0 {}
+
+        #[derive(Debug)]
+        enum Kind1 {
+            Foo,
         }
+        impl ErrorKind for Kind1 {
+            fn description(&self) -> &str {
+                "Foo happened"
+            }
+        }
+
+        assert_eq!(Kind0::Foo.error().to_string(), "Foo\nHISTORY:\n");
+        assert_eq!(
+            Kind1::Foo.cause("bar").to_string(),
+            "Foo happened (cause; bar)\nHISTORY:\n"
+        );
+        assert_eq!(Failed.error().to_string(), "Failed\nHISTORY:\n");
+
+        // Built-in kinds keep using the debugging forms
+        assert_eq!("Foo".to_string().error().to_string(), "\"Foo\"\nHISTORY:\n");
+        #[cfg(feature = "std")]
+        assert_eq!(
+            std::io::ErrorKind::NotFound.error().to_string(),
+            "NotFound\nHISTORY:\n"
+        );
     }
 
-    /// Makes a new `TrackableError` instance from `kind`.
-    ///
-    /// Note that the returning error has no cause.
-    fn from_kind(kind: K) -> Self {
-        TrackableError {
-            kind,
-            cause: None,
-            history: History::new(),
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn code_works() {
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+        struct Critical;
+        impl ErrorKind for Critical {
+            fn code(&self) -> Option<&str> {
+                Some("E1001")
+            }
+        }
+
+        assert_eq!(Failed.error().code(), None);
+        assert_eq!(
+            Failed.cause("foo").to_string(),
+            "Failed (cause; foo)\nHISTORY:\n"
+        );
+
+        let e = Critical.cause("foo");
+        assert_eq!(e.code(), Some("E1001"));
+        assert_eq!(e.to_string(), "Critical [E1001] (cause; foo)\nHISTORY:\n");
+        assert_eq!(format!("{:#}", e), "Critical [E1001] (cause; foo)");
+
+        #[cfg(feature = "serialize")]
+        {
+            extern crate serde_json;
+
+            let json = serde_json::to_value(Failed.error()).unwrap();
+            assert!(json.get("code").is_none());
+
+            let json = serde_json::to_value(&e).unwrap();
+            assert_eq!(json["code"], "E1001");
+
+            let d: TrackableError<Critical> = serde_json::from_value(json).unwrap();
+            assert_eq!(d.code(), Some("E1001"));
+            assert_eq!(d.to_string(), e.to_string());
         }
     }
 
-    /// Returns the kind of this error.
-    #[inline]
-    pub fn kind(&self) -> &K {
-        &self.kind
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn error_kind_error_works() {
+        #[derive(Debug, PartialEq)]
+        enum MyErrorKind {
+            NotFound,
+            Other,
+        }
+        impl ErrorKind for MyErrorKind {
+            fn description(&self) -> &str {
+                match *self {
+                    MyErrorKind::NotFound => "Not found",
+                    MyErrorKind::Other => DEFAULT_DESCRIPTION,
+                }
+            }
+        }
+
+        let e: Box<dyn Error> = Box::new(MyErrorKind::NotFound.into_std_error());
+        #[allow(deprecated)]
+        let description = e.description();
+        assert_eq!(description, "Not found");
+        assert_eq!(e.to_string(), "Not found");
+        assert!(e.source().is_none());
+        let kind = e.downcast_ref::<ErrorKindError<MyErrorKind>>().unwrap();
+        assert_eq!(*kind.kind(), MyErrorKind::NotFound);
+
+        let e: BoxError = Box::new(ErrorKindError::from(MyErrorKind::Other));
+        assert_eq!(e.to_string(), "Other");
+
+        let e = Failed.cause(MyErrorKind::Other.into_std_error());
+        assert_eq!(e.to_string(), "Failed (cause; Other)\nHISTORY:\n");
+        let cause = e.concrete_cause::<ErrorKindError<MyErrorKind>>().unwrap();
+        assert_eq!(*cause.kind(), MyErrorKind::Other);
     }
 
-    /// Tries to return the cause of this error as a value of `T` type.
-    ///
-    /// If neither this error has a cause nor it is an `T` value,
-    /// this method will return `None`.
-    #[inline]
-    pub fn concrete_cause<T>(&self) -> Option<&T>
-    where
-        T: Error + 'static,
-    {
-        self.cause.as_ref().and_then(|c| c.0.downcast_ref())
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn builder_works() {
+        let e = TrackableError::builder(Failed)
+            .cause("foo")
+            .context("id", 10)
+            .context("name", "bar")
+            .track_here()
+            .build();
+        let line = line!() - 2;
+        assert_eq!(
+            strip_thread(&e.to_string()),
+            format!(
+                "Failed (cause; foo)\nCONTEXT:\n  id: 10\n  name: bar\nHISTORY:\n  [0] at {}:{}:14\n",
+                file!(),
+                line
+            )
+        );
+
+        let e = TrackableError::builder(Failed)
+            .cause("foo")
+            .cause("bar")
+            .build();
+        assert_eq!(format!("{:#}", e), "Failed (cause; foo) (cause; bar)");
+        assert_eq!(e.track_count(), 0);
+        assert_eq!(
+            e.to_string(),
+            "Failed\nCAUSES:\n  [0] foo\n  [1] bar\nHISTORY:\n"
+        );
     }
-}
-impl<K: ErrorKind> From<K> for TrackableError<K> {
-    #[inline]
-    fn from(kind: K) -> Self {
-        Self::from_kind(kind)
+
+    #[test]
+    fn http_status_works() {
+        #[derive(Debug, Clone)]
+        struct Unavailable;
+        impl ErrorKind for Unavailable {
+            fn http_status(&self) -> Option<u16> {
+                Some(503)
+            }
+        }
+
+        assert_eq!(Failed.error().http_status(), None);
+        assert_eq!(Failure::from_error("foo").http_status(), None);
+
+        let e = track!(Unavailable.cause("foo"));
+        assert_eq!(e.http_status(), Some(503));
+        assert_eq!(e.clone().into_boxed_kind().http_status(), Some(503));
+        assert_eq!(Failed.takes_over(e).http_status(), None);
+        assert_eq!(
+            Unavailable.takes_over(Failed.error()).http_status(),
+            Some(503)
+        );
     }
-}
-impl<K: ErrorKind + Default> Default for TrackableError<K> {
-    #[inline]
-    fn default() -> Self {
-        Self::from_kind(K::default())
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialize_max_cause_len_works() {
+        extern crate serde_json;
+
+        let mut e = Failed.cause("a".repeat(1024 * 1024));
+        e.add_cause(format!("{}é", "b".repeat(1023)));
+        e.add_cause("short");
+        let json = serde_json::to_value(e.serialize_with_max_cause_len(1024)).unwrap();
+
+        let message = json["cause"]["message"].as_str().unwrap();
+        assert_eq!(
+            message,
+            format!("{}… (truncated {} bytes)", "a".repeat(1024), 1023 * 1024)
+        );
+        let message = json["additional_causes"][0]["message"].as_str().unwrap();
+        assert_eq!(
+            message,
+            format!("{}… (truncated 2 bytes)", "b".repeat(1023))
+        );
+        assert_eq!(json["additional_causes"][1]["message"], "short");
+
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(
+            json["cause"]["message"].as_str().unwrap().len(),
+            1024 * 1024
+        );
     }
-}
-impl<K: ErrorKind> fmt::Display for TrackableError<K> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.kind.display(f)?;
-        if let Some(ref e) = self.cause {
-            write!(f, " (cause; {})", e.0)?;
+
+    #[cfg(feature = "serialize")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn serialize_cause_type_name_works() {
+        extern crate serde_json;
+
+        let e = Failed.cause(std::fmt::Error);
+        assert_eq!(e.cause_type_name(), Some("core::fmt::Error"));
+
+        let json = serde_json::to_string(&e).unwrap();
+        let d: TrackableError<Failed> = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.cause_type_name(), Some("core::fmt::Error"));
+        assert_eq!(d.to_string(), e.to_string());
+
+        let json = serde_json::to_string(&d).unwrap();
+        let d: TrackableError<Failed> = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.cause_type_name(), Some("core::fmt::Error"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn suggestion_works() {
+        let e = Failed.cause("foo");
+        assert_eq!(e.suggestion(), None);
+        assert_eq!(format!("{:#}", e), "Failed (cause; foo)");
+
+        #[derive(Debug)]
+        struct RateLimited;
+        impl ErrorKind for RateLimited {
+            fn suggestion(&self) -> Option<&str> {
+                Some("retry after 30s")
+            }
         }
-        write!(f, "\n{}", self.history)?;
-        Ok(())
+
+        let e = RateLimited.cause("too many requests");
+        assert_eq!(e.suggestion(), Some("retry after 30s"));
+        assert_eq!(
+            format!("{:#}", e),
+            "RateLimited (cause; too many requests)\nsuggestion: retry after 30s"
+        );
+        assert_eq!(
+            e.to_string(),
+            "RateLimited (cause; too many requests)\nHISTORY:\n"
+        );
+
+        let e = e.into_boxed_kind();
+        assert_eq!(e.suggestion(), Some("retry after 30s"));
     }
-}
-impl<K: ErrorKind> Error for TrackableError<K> {
-    fn description(&self) -> &str {
-        self.kind.description()
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn user_message_works() {
+        let e = Failed.cause("foo");
+        assert_eq!(e.user_message(), None);
+        assert_eq!(format!("{:#}", e), "Failed (cause; foo)");
+
+        #[derive(Debug)]
+        struct Localized;
+        impl ErrorKind for Localized {
+            fn description(&self) -> &str {
+                "localized error"
+            }
+            fn user_message(&self) -> Option<String> {
+                Some("Une erreur est survenue".to_owned())
+            }
+        }
+
+        let e = Localized.cause("foo");
+        assert_eq!(e.user_message(), Some("Une erreur est survenue".to_owned()));
+        assert_eq!(format!("{:#}", e), "Une erreur est survenue (cause; foo)");
+        assert_eq!(e.to_string(), "localized error (cause; foo)\nHISTORY:\n");
+        assert_eq!(e.kind().description(), "localized error");
+
+        let e = e.into_boxed_kind();
+        assert_eq!(e.user_message(), Some("Une erreur est survenue".to_owned()));
+        assert_eq!(format!("{:#}", e), "Une erreur est survenue (cause; foo)");
     }
-    fn cause(&self) -> Option<&dyn Error> {
-        self.cause.as_ref().map::<&dyn Error, _>(|e| &**e.0)
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn set_history_works() {
+        let mut history = History::new();
+        history.add(Location::new("foo", "src/foo.rs", 10, "first"));
+        history.add(Location::new("bar", "src/bar.rs", 20, "second"));
+
+        let e = track!(Failed.cause("something wrong"), "discarded").with_history(history.clone());
+        assert_eq!(
+            strip_thread(&format!("\n{}", e)),
+            r#"
+Failed (cause; something wrong)
+HISTORY:
+  [0] at src/foo.rs:10 -- first
+  [1] at src/bar.rs:20 -- second
+"#
+        );
+
+        let mut e = e;
+        let old = e.set_history(History::new());
+        assert_eq!(old.events(), history.events());
+        assert_eq!(e.track_count(), 0);
     }
-}
-impl<K> Trackable for TrackableError<K> {
-    type Event = Location;
 
-    #[inline]
-    fn history(&self) -> Option<&History> {
-        Some(&self.history)
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn error_at_works() {
+        let location = Location::new("proxy", "src/upstream.rs", 27, "forwarded").with_column(5);
+        let e = track!(Failed.error_at(location.clone()), "here");
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], location);
+        assert_eq!(events[0].module_path(), "proxy");
+        assert_eq!(events[0].message(), "forwarded");
+        assert_eq!(events[1].message(), "here");
+        assert_eq!(e.causes().count(), 0);
     }
 
-    #[inline]
-    fn history_mut(&mut self) -> Option<&mut History> {
-        Some(&mut self.history)
+    #[cfg(feature = "std")]
+    #[test]
+    fn cause_boxed_works() {
+        let io_error = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let cause: BoxError = Box::new(io_error);
+        let ptr = &*cause as *const (dyn Error + Send + Sync) as *const u8;
+
+        let e = Failed.cause_boxed(cause);
+        let io_error = e.concrete_cause::<io::Error>().unwrap();
+        assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(io_error as *const io::Error as *const u8, ptr);
+        assert_eq!(format!("{:#}", e), "Failed (cause; no such file)");
+        assert!(e.concrete_cause::<fmt::Error>().is_none());
     }
-}
 
-#[derive(Debug, Clone)]
-struct Cause(Arc<BoxError>);
+    #[test]
+    fn cause_ref_works() {
+        let mut e = Failed.cause("something wrong");
+        e.add_cause("another");
+        assert_eq!(
+            e.cause_ref().map(ToString::to_string),
+            Some("something wrong".to_string())
+        );
 
-#[cfg(feature = "serialize")]
-mod impl_serde {
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
-    use std::sync::Arc;
+        let outer = Failed.cause(e);
+        let cause = outer.cause_ref().unwrap();
+        assert_eq!(cause.to_string().lines().next(), Some("Failed"));
+        assert!(cause.downcast_ref::<TrackableError<Failed>>().is_some());
 
-    use super::Cause;
+        assert!(Failed.error().cause_ref().is_none());
+    }
 
-    impl Serialize for Cause {
-        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-        where
-            S: Serializer,
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn parse_display_works() {
+        let mut e: Failure = track!(Failed.cause("something (really) wrong"), "first").into();
         {
-            serializer.serialize_str(&self.0.to_string())
+            let history = e.history_mut().unwrap();
+            history.add(Location::new("foo", "src/foo.rs", 10, "").with_function("foo::bar"));
+            history.add_dedup(Location::new("foo", "src/foo.rs", 20, "retry -- again"));
+            history.add_dedup(Location::new("foo", "src/foo.rs", 20, "retry -- again"));
+            history.add(Location::new("foo", "src/foo.rs", 30, "multi\n\nline").with_column(7));
         }
-    }
-    impl<'de> Deserialize<'de> for Cause {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: Deserializer<'de>,
+        let s = strip_thread(&e.to_string());
+        let parsed = Failure::parse_display(&s).unwrap();
+        assert_eq!(strip_thread(&parsed.to_string()), s);
+
+        let events = parsed.history().unwrap().events();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].message(), "first");
+        assert_eq!(events[1].function(), Some("foo::bar"));
+        assert_eq!(events[2].repeat_count(), 2);
+        assert_eq!(events[2].message(), "retry -- again");
+        assert_eq!(events[3].column(), 7);
+        assert_eq!(events[3].message(), "multi\n\nline");
+        assert_eq!(events[3].module_path(), "");
+        assert_eq!(
+            format!("{:#}", parsed),
+            "Failed (cause; something (really) wrong)"
+        );
+
+        // Elided entries
+        let mut e = Failure::from(Failed.error());
         {
-            let s = String::deserialize(deserializer)?;
-            Ok(Cause(Arc::new(s.into())))
+            let history = e.history_mut().unwrap();
+            history.set_max_len(2);
+            for i in 0..12 {
+                history.add(Location::new("foo", "src/foo.rs", i, "a\nb"));
+            }
         }
+        let s = strip_thread(&e.to_string());
+        assert_eq!(
+            strip_thread(&Failure::parse_display(&s).unwrap().to_string()),
+            s
+        );
+
+        // Errors
+        let error = |s| Failure::parse_display(s).unwrap_err();
+        assert_eq!(error("").line(), 1);
+        assert_eq!(error("Critical\nHISTORY:\n").line(), 1);
+        assert_eq!(
+            error("Failed (cause; foo)\n  caused by: bar\nHISTORY:\n").line(),
+            2
+        );
+        assert_eq!(
+            error("Failed\nHISTORY:\n  [1] at src/foo.rs:10\n").line(),
+            3
+        );
+        assert_eq!(error("Failed\nHISTORY:\n  [0] at src/foo.rs\n").line(), 3);
+        assert_eq!(
+            error("Failed\nHISTORY:\n  [0] at src/foo.rs:1\n  ... 1 earlier entries elided\n")
+                .line(),
+            4
+        );
+        assert_eq!(
+            error("Failed\nHISTORY:\n  [0] at src/foo.rs:1\nfoo\n").to_string(),
+            "cannot parse line 4: unexpected line"
+        );
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std;
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn freeze_works() {
+        let e = track!(Failed.cause("something wrong"), "first");
+        let e = track!(e, "second");
+        let expected = e.to_string();
+
+        let frozen = e.freeze();
+        let a = frozen.clone();
+        let b = frozen.clone();
+        assert!(FrozenError::ptr_eq(&a, &b));
+        assert!(std::ptr::eq(a.history().unwrap(), b.history().unwrap()));
+        assert_eq!(a.to_string(), expected);
+        assert_eq!(b.to_string(), expected);
+        assert_eq!(a.history().unwrap().len(), 2);
+        assert_eq!(a.kind(), &Failed);
+        assert!(a.source().is_some());
+
+        // Tracking a frozen error is a no-op
+        let a = track!(a, "third");
+        assert_eq!(a.history_len(), 2);
+        assert_eq!(a.to_string(), expected);
 
+        // Thawing copies the error only if it is shared
+        let thawed = track!(a.thaw(), "third");
+        assert_eq!(thawed.history().unwrap().len(), 3);
+        assert_eq!(b.history_len(), 2);
+        drop(frozen);
+        let thawed = b.thaw();
+        assert_eq!(thawed.to_string(), expected);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
     #[test]
-    fn it_works() {
-        #[derive(Debug, TrackableError)]
-        #[trackable(error_kind = "MyErrorKind")]
-        struct MyError(TrackableError<MyErrorKind>);
-        impl From<std::io::Error> for MyError {
-            fn from(f: std::io::Error) -> Self {
-                // Any I/O errors are considered critical
-                MyErrorKind::Critical.cause(f).into()
+    fn failure_ord_works() {
+        let mut failures = [
+            Failure::from("c"),
+            track!(Failure::from("a")),
+            Failed.error().into(),
+            Failure::from("b"),
+            Failure::from("a"),
+        ];
+        failures.sort();
+        let causes = failures
+            .iter()
+            .map(|f| f.causes().map(|e| e.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(causes, [vec![], vec!["a"], vec!["a"], vec!["b"], vec!["c"]]);
+
+        // The sort is stable, and histories are ignored
+        assert_eq!(failures[1].history().unwrap().len(), 1);
+        assert_eq!(failures[2].history().unwrap().len(), 0);
+        assert_eq!(failures[1], failures[2]);
+        assert_ne!(failures[2], failures[3]);
+        assert!(failures[3] < failures[4]);
+
+        let mut e = Failed.cause("a");
+        e.add_cause("b");
+        assert!(Failure::from(e) > Failure::from("a"));
+    }
+
+    #[test]
+    fn timeout_works() {
+        let e = Failure::timeout("connecting to db0");
+        assert!(e.is_timeout());
+        assert_eq!(
+            e.concrete_cause::<Timeout>().map(|t| t.message()),
+            Some("connecting to db0")
+        );
+        assert_eq!(
+            format!("{:#}", e),
+            "Failed (cause; timed out: connecting to db0)"
+        );
+        assert_eq!(
+            format!("{:#}", Failure::timeout("")),
+            "Failed (cause; timed out)"
+        );
+
+        // Kept while tracking and taking over
+        let e = track!(e);
+        assert!(e.is_timeout());
+        assert!(Failed.takes_over(e).is_timeout());
+
+        assert!(!Failed.error().is_timeout());
+        assert!(!Failure::from("foo").is_timeout());
+        assert!(!Failed.error().into_boxed_kind().is_timeout());
+
+        #[derive(Debug)]
+        struct Deadline;
+        impl ErrorKind for Deadline {
+            fn is_timeout(&self) -> bool {
+                true
             }
         }
+        assert!(Deadline.error().is_timeout());
+        assert!(Deadline.error().into_boxed_kind().is_timeout());
+    }
 
-        #[allow(dead_code)]
-        #[derive(Debug, PartialEq, Eq)]
-        enum MyErrorKind {
-            Critical,
-            NonCritical,
-        }
-        impl ErrorKind for MyErrorKind {}
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_timeout_works() {
+        let e: IoError = io::Error::new(io::ErrorKind::TimedOut, "foo").into();
+        assert!(e.is_timeout());
+        let e: IoError = io::Error::new(io::ErrorKind::NotFound, "foo").into();
+        assert!(!e.is_timeout());
+    }
 
-        // Tracks an error
-        let error: MyError = MyErrorKind::Critical.cause("something wrong").into();
-        let error = track!(error);
-        let error = track!(error, "I passed here");
+    #[test]
+    fn to_logfmt_works() {
+        let e: TrackableError<Failed> = Failed.error();
+        assert_eq!(e.to_logfmt(), r#"kind="Failed" history_len=0"#);
+
+        #[derive(Debug)]
+        struct Critical;
+        impl ErrorKind for Critical {}
+
+        let mut e = TrackableError::new(Critical, "something \"wrong\"\n\tat C:\\foo");
+        e.history_mut()
+            .unwrap()
+            .add(Location::new("foo", "src/x.rs", 10, "a"));
+        e.history_mut()
+            .unwrap()
+            .add(Location::new("foo", "src/x.rs", 27, "b"));
+        let s = e.to_logfmt();
+        assert!(!s.contains('\n'));
         assert_eq!(
-            format!("\nError: {}", error).replace('\\', "/"),
-            r#"
-Error: Critical (cause; something wrong)
-HISTORY:
-  [0] at src/error.rs:508
-  [1] at src/error.rs:509 -- I passed here
-"#
+            s,
+            r#"kind="Critical" cause="something \"wrong\"\n\tat C:\\foo" history_len=2 last_location="src/x.rs:27""#
         );
 
-        // Tries to execute I/O operation
-        let result = (|| -> Result<_, MyError> {
-            let f =
-                track!(std::fs::File::open("/path/to/non_existent_file").map_err(MyError::from,))?;
-            Ok(f)
-        })();
-        let error = result.err().unwrap();
-        let cause = error.concrete_cause::<std::io::Error>().unwrap();
-        assert_eq!(cause.kind(), std::io::ErrorKind::NotFound);
+        e.add_cause("another");
+        e.history_mut()
+            .unwrap()
+            .add(Location::new("foo", "src/y.rs", 3, "").with_column(5));
+        assert_eq!(
+            e.to_logfmt(),
+            r#"kind="Critical" cause="something \"wrong\"\n\tat C:\\foo" cause="another" history_len=3 last_location="src/y.rs:3:5""#
+        );
     }
 }
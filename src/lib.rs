@@ -30,9 +30,9 @@
 //!     assert_eq!(format!("\r{}", error).replace('\\', "/"), r#"
 //! Failed (cause; No such file or directory)
 //! HISTORY:
-//!   [0] at src/lib.rs:7
-//!   [1] at src/lib.rs:12
-//!   [2] at src/lib.rs:16
+//!   [0] at src/lib.rs:7:5
+//!   [1] at src/lib.rs:12:5
+//!   [2] at src/lib.rs:16:5
 //! "#);
 //! }
 //! ```
@@ -40,23 +40,124 @@
 //! This example used the built-in `Failure` type,
 //! but you can easily define your own trackable error types.
 //! See the documentaion of [error](error/index.html) module for more details.
+//!
+//! # `no_std` support
+//!
+//! This crate depends on `std` by default.
+//! If the `std` feature is disabled, it can be used in `#![no_std]` environments that have `alloc`.
+//! In that case, `IoError` and the `backtrace`, `time` and `thread` features are unavailable.
+//!
+//! # Inline histories
+//!
+//! If the `smallvec` feature is enabled, a [History](struct.History.html) stores up to
+//! 4 events inline (i.e., without heap allocation), and moves them to the heap
+//! when more events are added.
+//! This reduces the allocations for errors that are tracked only a few times,
+//! at the cost of a larger `TrackableError` (as it holds the inline storage).
+//! The API of `History` is the same regardless of the feature.
+//!
+//! # Sequence numbers
+//!
+//! If the `seq` feature is enabled, every [Location](struct.Location.html) is stamped with
+//! a number taken from a crate-global counter (see [`Location::seq`](struct.Location.html#method.seq)).
+//! The numbers give a total order of the tracked locations across threads.
+//! They are serialized with the locations and shown in the alternate `Display` form of locations
+//! (e.g., `at src/foo.rs:10 (foo) {seq: 17}`), while the default `Display` form is left unchanged.
+//! (A history entry is not rendered as `[0]#17 at ...`, as that would change the default form
+//! of every error, which [`Failure::parse_display`](error/struct.Failure.html#method.parse_display)
+//! and exact-output assertions rely on.)
+//!
+//! # Disabling tracking
+//!
+//! If the `disable_tracking` feature is enabled, every [track!](macro.track.html) invocation
+//! (and the macros built on it) becomes a pure pass-through:
+//! no `Location` is constructed and nothing is added to histories.
+//! Histories still exist, but they stay empty.
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+// Errors get larger by the inline storage of their histories
+#![cfg_attr(all(test, feature = "smallvec"), allow(clippy::result_large_err))]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(all(not(feature = "std"), test))]
+#[macro_use]
+extern crate std;
 #[cfg(feature = "serialize")]
 extern crate serde;
 #[cfg(feature = "serialize")]
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "anyhow")]
+extern crate anyhow;
+#[cfg(feature = "log")]
+extern crate log;
+#[macro_use]
+extern crate pin_project_lite;
+#[cfg(feature = "smallvec")]
+extern crate smallvec;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 #[macro_use]
 extern crate trackable_derive;
 
 use std::borrow::Cow;
 use std::fmt;
-use std::task::Poll;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use std::io;
+use std::panic;
+use std::pin::Pin;
+#[cfg(all(feature = "seq", target_has_atomic = "64"))]
+use std::sync::atomic::AtomicU64;
+#[cfg(all(feature = "seq", not(target_has_atomic = "64")))]
+use std::sync::atomic::AtomicUsize;
+#[cfg(feature = "seq")]
+use std::sync::atomic::Ordering;
+use std::task::{Context, Poll};
+#[cfg(feature = "thread")]
+use std::thread::{self, ThreadId};
+#[cfg(feature = "time")]
+use std::time::SystemTime;
 
 #[doc(hidden)]
 pub use trackable_derive::*;
 
+#[doc(hidden)]
+pub use std::format;
+
+#[doc(hidden)]
+#[inline]
+#[must_use = "a tracked value should be used (e.g., returned or propagated by `?`)"]
+pub fn __must_use<T>(value: T) -> T {
+    value
+}
+
+#[doc(hidden)]
+pub const TRACKING_ENABLED: bool = !cfg!(feature = "disable_tracking");
+
+// for `trackable_derive` and the `std::` paths in this crate
+#[cfg(all(not(feature = "std"), not(test)))]
+mod std {
+    pub use alloc::{borrow, fmt, format};
+    pub use core::{any, cmp, error, future, hash, iter, mem, ops, panic, pin, task};
+    pub mod sync {
+        pub use alloc::sync::*;
+        #[cfg(feature = "seq")]
+        pub use core::sync::atomic;
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod prelude {
+    pub use alloc::boxed::Box;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec::Vec;
+}
+#[cfg(not(feature = "std"))]
+use prelude::*;
+
 #[macro_use]
 mod macros;
 
@@ -77,6 +178,10 @@ pub mod result;
 ///
 /// See [`TrackableError`](error/struct.TrackableError.html) as a typical implementaion of this trait.
 ///
+/// This trait is also implemented for `Option<T>`, `Result<T, E>` and `Poll<T>`
+/// (where `T` and `E` are trackable), so generic code bounded on `Trackable` can accept them uniformly.
+/// Their histories are the ones of the contained trackable values (i.e., `None` for `Ok(_)`).
+///
 /// # Examples
 ///
 /// Defines a trackable type.
@@ -107,16 +212,22 @@ pub mod result;
 ///     let o = track!(o, "Hello");
 ///     let o = track!(o, "Hello {}", "World!");
 ///
+/// #     if cfg!(feature = "disable_tracking") { return; }
 ///     assert_eq!(format!("\n{}", o.history).replace('\\', "/"), r#"
 /// HISTORY:
-///   [0] at src/lib.rs:23
-///   [1] at src/lib.rs:24 -- Hello
-///   [2] at src/lib.rs:25 -- Hello World!
+///   [0] at src/lib.rs:22:13
+///   [1] at src/lib.rs:23:13 -- Hello
+///   [2] at src/lib.rs:24:13 -- Hello World!
 /// "#);
 /// }
 /// ```
 pub trait Trackable {
     /// Event type which a history of an instance of this type can have.
+    ///
+    /// Tracking macros create an event by converting a [`Location`](struct.Location.html)
+    /// with `From::from`, so a custom event type can carry more information than `Location`
+    /// by implementing `From<Location>`.
+    /// `Location` itself is available as an event type thanks to the reflexive `From` implementation.
     type Event: From<Location>;
 
     /// Add an event into the tail of the history of this instance.
@@ -132,12 +243,73 @@ pub trait Trackable {
         }
     }
 
+    /// Adds the location of the caller into the tail of the history of this instance.
+    ///
+    /// This is a function-based alternative to [track!](macro.track.html) macro
+    /// that relies on `#[track_caller]`, so it can be used in closures and generic helpers.
+    /// Note that the resulting location has no module path.
+    ///
+    /// When this is called through a function pointer (e.g., `for_each(Trackable::track_here)`),
+    /// the caller location cannot be captured, so wrap the call in a closure instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let mut errors = vec![Failed.error(), Failed.error()];
+    /// errors.iter_mut().for_each(|e| e.track_here());
+    ///
+    /// let location = &errors[0].history().unwrap().events()[0];
+    /// assert_eq!(location.file(), file!());
+    /// assert_eq!(location.line(), line!() - 4);
+    /// ```
+    #[track_caller]
+    fn track_here(&mut self) {
+        let caller = panic::Location::caller();
+        self.track(|| From::from(Location::from_caller(caller, "")))
+    }
+
+    /// Adds the location of the caller into the tail of the history of this instance, and returns it.
+    ///
+    /// This is a chainable variant of [`track_here`](#method.track_here)
+    /// (the name `track` is taken by the method that takes an event factory).
+    /// If this instance is not being tracked, it is returned untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = Failed.error().tracked().tracked();
+    /// assert_eq!(e.history_len(), 2);
+    /// ```
+    #[track_caller]
+    #[must_use]
+    fn tracked(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.track_here();
+        self
+    }
+
     /// Returns `true` if it is being tracked, otherwise `false`.
     #[inline]
     fn in_tracking(&self) -> bool {
         self.history().is_some()
     }
 
+    /// Returns the number of the events in the tracking history of this instance.
+    ///
+    /// If it is not being tracked, this will return `0`.
+    #[inline]
+    fn history_len(&self) -> usize {
+        self.history().map_or(0, History::len)
+    }
+
     /// Returns the reference of the tracking history of this instance.
     ///
     /// If it is not being tracked, this will return `None.
@@ -197,6 +369,135 @@ impl<T: Trackable> Trackable for Poll<T> {
     }
 }
 
+/// An extension trait for `Result<T, E: Trackable>` that provides method-based tracking.
+///
+/// The methods of this trait record the location of the caller
+/// (captured via `#[track_caller]`) instead of a macro expansion site.
+/// Unlike [`track!`](macro.track.html), the resulting locations have no module path.
+///
+/// # Examples
+///
+/// ```
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::TrackableResultExt;
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// fn foo() -> Result<(), Failure> {
+///     Err(Failed.error().into())
+/// }
+///
+/// let e = foo().track().track_msg("retrying").err().unwrap();
+/// # if cfg!(feature = "thread") { return; }
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/lib.rs:13:15
+///   [1] at src/lib.rs:13:23 -- retrying
+/// "#);
+/// ```
+pub trait TrackableResultExt: Sized {
+    /// Tracks the location of the caller into the history of the error (if any).
+    fn track(self) -> Self;
+
+    /// Tracks the location of the caller with `message` into the history of the error (if any).
+    ///
+    /// `message` is formatted only if this is an error.
+    fn track_msg<M: fmt::Display>(self, message: M) -> Self;
+}
+impl<T, E: Trackable> TrackableResultExt for Result<T, E> {
+    #[track_caller]
+    fn track(mut self) -> Self {
+        self.track_here();
+        self
+    }
+
+    #[track_caller]
+    fn track_msg<M: fmt::Display>(mut self, message: M) -> Self {
+        let location = panic::Location::caller();
+        Trackable::track(&mut self, || {
+            From::from(Location::from_caller(location, message.to_string()))
+        });
+        self
+    }
+}
+
+/// Tracks the location of the caller into the history of the error of `result` (if any),
+/// and returns `result`.
+///
+/// This is a function form of [`TrackableResultExt::track`](trait.TrackableResultExt.html#tymethod.track)
+/// for codebases that prefer explicit function calls.
+/// The location is captured via `#[track_caller]`, so it has no module path.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::{try_track, Trackable};
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// fn foo() -> Result<(), Failure> {
+///     Err(Failed.error().into())
+/// }
+///
+/// fn bar() -> Result<(), Failure> {
+///     try_track(foo())?;
+///     Ok(())
+/// }
+///
+/// let e = bar().err().unwrap();
+/// let location = &e.history().unwrap().events()[0];
+/// assert_eq!(location.file(), file!());
+/// assert_eq!(location.line(), 13);
+/// ```
+#[track_caller]
+pub fn try_track<T, E: Trackable>(result: Result<T, E>) -> Result<T, E> {
+    TrackableResultExt::track(result)
+}
+
+pin_project! {
+    /// A future that tracks a location into the history of its output when it completes.
+    ///
+    /// This is created by [track_await!](macro.track_await.html).
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct TrackAwait<F, L> {
+        #[pin]
+        future: F,
+        location: Option<L>,
+    }
+}
+impl<F, L> TrackAwait<F, L> {
+    #[doc(hidden)]
+    pub fn new(future: F, location: Option<L>) -> Self {
+        TrackAwait { future, location }
+    }
+}
+impl<F, L> fmt::Debug for TrackAwait<F, L> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TrackAwait").finish()
+    }
+}
+impl<F, L> Future for TrackAwait<F, L>
+where
+    F: Future,
+    F::Output: Trackable,
+    <F::Output as Trackable>::Event: From<Location>,
+    L: FnOnce() -> Location,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        match this.future.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(mut output) => {
+                if let Some(location) = this.location.take() {
+                    output.track(|| From::from(location()));
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
 /// The tracking history of a target.
 ///
 /// A history is a sequence of the tracked events.
@@ -224,159 +525,2756 @@ impl<T: Trackable> Trackable for Poll<T> {
 ///   [1] event: bar
 /// "#);
 /// ```
+///
+/// If the `serialize` feature is enabled, a history is serialized as a sequence of its events.
+/// Note that the maximum length and the number of elided events are not serialized.
+///
+/// If the `smallvec` feature is enabled, up to 4 events are stored inline
+/// (see [the crate documentation](index.html#inline-histories)).
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-pub struct History<Event>(Vec<Event>);
+pub struct History<Event> {
+    events: Events<Event>,
+    max_len: usize, // `usize::MAX` means unlimited
+    elided: usize,
+}
+
+/// The number of the events stored inline in a history if the `smallvec` feature is enabled.
+#[cfg(feature = "smallvec")]
+const HISTORY_INLINE_CAPACITY: usize = 4;
+
+#[cfg(feature = "smallvec")]
+type Events<Event> = smallvec::SmallVec<[Event; HISTORY_INLINE_CAPACITY]>;
+#[cfg(not(feature = "smallvec"))]
+type Events<Event> = Vec<Event>;
+
 impl<Event> History<Event> {
     /// Makes an empty history.
+    ///
+    /// This does not allocate until the first event is added.
     #[inline]
     pub fn new() -> Self {
-        History(Vec::new())
+        History {
+            events: Events::new(),
+            max_len: usize::MAX,
+            elided: 0,
+        }
+    }
+
+    /// Makes an empty history with at least the specified capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let history = History::<Location>::with_capacity(8);
+    /// assert!(history.capacity() >= 8);
+    /// assert!(history.is_empty());
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        History {
+            events: Events::with_capacity(capacity),
+            max_len: usize::MAX,
+            elided: 0,
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more events.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.events.reserve(additional);
+    }
+
+    /// Returns the number of the events this history can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.events.capacity()
     }
 
     /// Adds an event to the tail of this history.
+    ///
+    /// If the length of this history exceeds the maximum length,
+    /// the oldest event will be elided.
     #[inline]
     pub fn add(&mut self, event: Event) {
-        self.0.push(event);
+        self.events.push(event);
+        self.truncate();
+    }
+
+    /// Inserts an event at position `index` of this history, shifting the following events.
+    ///
+    /// If the length of this history exceeds the maximum length,
+    /// the oldest event will be elided (even if it is the inserted one).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > self.len()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// history.add("bar");
+    /// history.insert(0, "foo");
+    /// assert_eq!(history.events(), ["foo", "bar"]);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, index: usize, event: Event) {
+        self.events.insert(index, event);
+        self.truncate();
     }
 
     /// Returns the tracked events in this history.
+    ///
+    /// Elided events are not included.
     #[inline]
     pub fn events(&self) -> &[Event] {
-        &self.0[..]
+        &self.events[..]
     }
-}
-impl<Event: fmt::Display> fmt::Display for History<Event> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "HISTORY:")?;
-        for (i, e) in self.events().iter().enumerate() {
-            writeln!(f, "  [{}] {}", i, e)?;
-        }
-        Ok(())
+
+    /// Returns an iterator over the tracked events in this history from the most recent one.
+    ///
+    /// Elided events are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// history.add("foo");
+    /// history.add("bar");
+    /// assert_eq!(history.events_rev().collect::<Vec<_>>(), [&"bar", &"foo"]);
+    /// ```
+    #[inline]
+    pub fn events_rev(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter().rev()
     }
-}
-impl<Event> Default for History<Event> {
+
+    /// Returns the oldest event in this history.
+    ///
+    /// If some events have been elided, this returns the oldest one of the retained events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// assert_eq!(history.first(), None);
+    ///
+    /// history.add("foo");
+    /// history.add("bar");
+    /// assert_eq!(history.first(), Some(&"foo"));
+    /// ```
     #[inline]
-    fn default() -> Self {
-        History::new()
+    pub fn first(&self) -> Option<&Event> {
+        self.events.first()
     }
-}
 
-/// The location of interest in source code files.
-///
-/// Typically this is created in the macros which defined in this crate.
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-pub struct Location {
-    module_path: Cow<'static, str>,
-    file: Cow<'static, str>,
-    line: u32,
-    message: Cow<'static, str>,
-}
-impl Location {
-    /// Makes a new `Location` instance.
+    /// Returns the most recent event in this history.
     ///
     /// # Examples
     ///
     /// ```
-    /// use trackable::Location;
+    /// use trackable::History;
     ///
-    /// let location = Location::new(module_path!(), file!(), line!(), "Hello".to_string());
-    /// assert_eq!(location.message(), "Hello");
+    /// let mut history = History::new();
+    /// assert_eq!(history.last(), None);
+    ///
+    /// history.add("foo");
+    /// history.add("bar");
+    /// assert_eq!(history.last(), Some(&"bar"));
     /// ```
     #[inline]
-    pub fn new<M, F, T>(module_path: M, file: F, line: u32, message: T) -> Self
-    where
-        M: Into<Cow<'static, str>>,
-        F: Into<Cow<'static, str>>,
-        T: Into<Cow<'static, str>>,
-    {
-        Location {
-            module_path: module_path.into(),
-            file: file.into(),
-            line,
-            message: message.into(),
-        }
+    pub fn last(&self) -> Option<&Event> {
+        self.events.last()
     }
 
-    /// Gets the crate name of this location.
+    /// Returns the number of the events in this history.
+    ///
+    /// Elided events are not counted.
     #[inline]
-    pub fn crate_name(&self) -> &str {
-        if let Some(module_path_end) = self.module_path.find(':') {
-            &self.module_path[..module_path_end]
-        } else {
-            self.module_path.as_ref()
-        }
+    pub fn len(&self) -> usize {
+        self.events.len()
     }
 
-    /// Gets the module path of this location.
+    /// Returns `true` if this history has no events, otherwise `false`.
     #[inline]
-    pub fn module_path(&self) -> &str {
-        self.module_path.as_ref()
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
     }
 
-    /// Gets the file name of this location.
+    /// Sets the maximum number of the events retained in this history.
+    ///
+    /// If the length of this history exceeds `max_len`, the oldest events are dropped
+    /// and a line like `... 3 earlier entries elided` is shown in the `Display` form instead of them.
+    ///
+    /// By default, the length of a history is unlimited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// history.set_max_len(2);
+    /// for i in 0..5 {
+    ///     history.add(i);
+    /// }
+    /// assert_eq!(history.events(), [3, 4]);
+    /// assert_eq!(history.elided_count(), 3);
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   ... 3 earlier entries elided
+    ///   [3] 3
+    ///   [4] 4
+    /// "#);
+    /// ```
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = max_len;
+        self.truncate();
+    }
+
+    /// Returns the maximum number of the events retained in this history.
+    ///
+    /// `None` means that the length is unlimited.
     #[inline]
-    pub fn file(&self) -> &str {
-        self.file.as_ref()
+    pub fn max_len(&self) -> Option<usize> {
+        if self.max_len == usize::MAX {
+            None
+        } else {
+            Some(self.max_len)
+        }
     }
 
-    /// Gets the line of this location.
+    /// Returns the number of the events elided from this history.
     #[inline]
-    pub fn line(&self) -> u32 {
-        self.line
+    pub fn elided_count(&self) -> usize {
+        self.elided
     }
 
-    /// Gets the message left at this location.
+    /// Returns a marker of the current position of this history.
+    ///
+    /// This is the number of the events added to this history so far
+    /// (i.e., `self.len()` plus the elided count).
+    /// It can be passed to [`entries_since`](#method.entries_since) later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// history.add("foo");
+    ///
+    /// let checkpoint = history.checkpoint();
+    /// history.add("bar");
+    /// history.add("baz");
+    /// assert_eq!(history.entries_since(checkpoint), ["bar", "baz"]);
+    /// ```
     #[inline]
-    pub fn message(&self) -> &str {
-        self.message.as_ref()
+    pub fn checkpoint(&self) -> usize {
+        self.elided + self.events.len()
     }
-}
-impl fmt::Display for Location {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "at {}:{}", self.file(), self.line())?;
-        if !self.message().is_empty() {
-            write!(f, " -- {}", self.message())?;
-        }
-        Ok(())
+
+    /// Returns the events added after `checkpoint` was taken by [`checkpoint`](#method.checkpoint).
+    ///
+    /// The events elided by `set_max_len` are not included.
+    pub fn entries_since(&self, checkpoint: usize) -> &[Event] {
+        let start = checkpoint.saturating_sub(self.elided);
+        &self.events[start.min(self.events.len())..]
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use error::Failure;
+    /// Retains only the events specified by the predicate `f`.
+    ///
+    /// This is the same as `Vec::retain`.
+    /// The number of the elided events is not changed, so the remaining entries are numbered
+    /// contiguously from it in the `Display` form (i.e., from `[0]` if no events have been elided).
+    /// The checkpoints taken before calling this method are no longer valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// for i in 0..5 {
+    ///     history.add(i);
+    /// }
+    /// history.retain(|&i| i % 2 == 0);
+    /// assert_eq!(history.events(), [0, 2, 4]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Event) -> bool,
+    {
+        self.events.retain(|e| f(e));
+    }
 
+    /// Removes all the events (including the elided count) from this history.
+    ///
+    /// The allocated capacity and the maximum length are retained,
+    /// so the history can be reused without reallocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// history.add("foo");
+    /// history.clear();
+    /// assert!(history.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.elided = 0;
+    }
+
+    /// Concatenates this history and `other`.
+    ///
+    /// The events of `other` are appended after the ones of this history (i.e., the ordering is preserved).
+    /// The maximum length of this history is also applied to the resulting history,
+    /// while the maximum length and the elided count of `other` are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut a = History::new();
+    /// a.add("foo");
+    /// let mut b = History::new();
+    /// b.add("bar");
+    ///
+    /// let history = a.join(b);
+    /// assert_eq!(history.events(), ["foo", "bar"]);
+    /// ```
+    pub fn join(mut self, other: History<Event>) -> Self {
+        self.append(other);
+        self
+    }
+
+    /// Appends clones of the events of `other` after the ones of this history.
+    ///
+    /// This is the borrowing version of [`join`](#method.join).
+    pub fn extend_from(&mut self, other: &History<Event>)
+    where
+        Event: Clone,
+    {
+        for event in &other.events {
+            self.add(event.clone());
+        }
+    }
+
+    pub(crate) fn append(&mut self, other: History<Event>) {
+        for event in other.events {
+            self.add(event);
+        }
+    }
+
+    fn truncate(&mut self) {
+        if self.events.len() > self.max_len {
+            let excess = self.events.len() - self.max_len;
+            self.events.drain(..excess);
+            self.elided += excess;
+        }
+    }
+
+    fn fmt_elided(&self, f: &mut fmt::Formatter, indent: &str) -> fmt::Result {
+        if self.elided > 0 {
+            writeln!(f, "{}... {} earlier entries elided", indent, self.elided)?;
+        }
+        Ok(())
+    }
+}
+impl<Event: fmt::Display> History<Event> {
+    /// Writes the `Display` form of this history to `writer`.
+    ///
+    /// The output is streamed to `writer` without building an intermediate string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// history.add("foo");
+    ///
+    /// let mut buf = Vec::new();
+    /// history.write_to(&mut buf).unwrap();
+    /// assert_eq!(buf, b"HISTORY:\n  [0] foo\n");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+impl<Event: fmt::Display> fmt::Display for History<Event> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "HISTORY:")?;
+        self.fmt_elided(f, "  ")?;
+        for (i, e) in self.events().iter().enumerate() {
+            let index = self.elided + i;
+            let e = Indented(e, "  ", index_width(index));
+            if f.alternate() {
+                writeln!(f, "  [{}] {:#}", index, e)?;
+            } else {
+                writeln!(f, "  [{}] {}", index, e)?;
+            }
+        }
+        Ok(())
+    }
+}
+impl<Event> Default for History<Event> {
+    #[inline]
+    fn default() -> Self {
+        History::new()
+    }
+}
+impl History<Location> {
+    /// Returns `true` if the message of any location in this history contains `needle`,
+    /// otherwise `false`.
+    ///
+    /// This is useful to check the history of an error in tests
+    /// without depending on its `Display` form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// use trackable::Trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = track!(Failed.error(), "connecting to {}", "db0");
+    /// let line = line!() - 1;
+    ///
+    /// # if cfg!(feature = "disable_tracking") { return; }
+    /// let history = e.history().unwrap();
+    /// assert!(history.contains_message("to db0"));
+    /// assert!(!history.contains_message("db1"));
+    /// assert!(history.contains_location(file!(), line));
+    /// assert!(!history.contains_location(file!(), line + 1));
+    /// # }
+    /// ```
+    pub fn contains_message(&self, needle: &str) -> bool {
+        self.events.iter().any(|l| l.message().contains(needle))
+    }
+
+    /// Returns `true` if this history has a location at the line `line` of the file `file`,
+    /// otherwise `false`.
+    pub fn contains_location(&self, file: &str, line: u32) -> bool {
+        self.events
+            .iter()
+            .any(|l| l.file() == file && l.line() == line)
+    }
+
+    /// Returns an iterator over the locations in this history that have the tag `tag`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// # fn main() {
+    /// use trackable::Trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let e = track!(Failed.error(), tag = "retry");
+    /// let e = track!(e, tag = "fallback", "using {}", "cache");
+    /// let e = track!(e);
+    ///
+    /// let history = e.history().unwrap();
+    /// # if cfg!(feature = "disable_tracking") { return; }
+    /// let fallbacks = history.entries_with_tag("fallback").collect::<Vec<_>>();
+    /// assert_eq!(fallbacks.len(), 1);
+    /// assert_eq!(fallbacks[0].message(), "using cache");
+    /// # }
+    /// ```
+    pub fn entries_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Location> + 'a {
+        self.events.iter().filter(move |l| l.tag() == Some(tag))
+    }
+
+    /// Adds a location to the tail of this history unless it is the same as the last one.
+    ///
+    /// If `location` has the same file, line, column, module path and message as the last location,
+    /// the repeat count of the last one is incremented instead of adding a new entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let mut history = History::new();
+    /// for _ in 0..3 {
+    ///     history.add_dedup(Location::new("foo", "src/foo.rs", 42, ""));
+    /// }
+    /// history.add_dedup(Location::new("foo", "src/foo.rs", 42, "retry failed"));
+    ///
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   [0] at src/foo.rs:42 (x3)
+    ///   [1] at src/foo.rs:42 -- retry failed
+    /// "#);
+    /// ```
+    pub fn add_dedup(&mut self, location: Location) {
+        if let Some(last) = self.events.last_mut() {
+            if last.is_same_site(&location) {
+                last.repeat_count = last.repeat_count.saturating_add(1);
+                return;
+            }
+        }
+        self.add(location);
+    }
+
+    /// Parses the entries of the `Display` form of a history (i.e., the lines following the header).
+    ///
+    /// This is the inverse of `fmt_locations` with the default options.
+    /// On failure, the index of the offending line and the reason are returned.
+    pub(crate) fn parse_entries<'a, I>(lines: I) -> Result<Self, (usize, &'static str)>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut history = History::new();
+        let mut width = 0;
+        for (i, line) in lines.into_iter().enumerate() {
+            if let Some(rest) = line.strip_prefix("  [") {
+                let (index, rest) = rest
+                    .split_once("] at ")
+                    .ok_or((i, "invalid history entry"))?;
+                let index = index
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n == history.elided + history.len())
+                    .ok_or((i, "unexpected entry index"))?;
+                let location = Location::parse_entry(rest).ok_or((i, "invalid location"))?;
+                history.events.push(location);
+                width = index_width(index);
+            } else if let Some(rest) = line.strip_prefix("  ... ") {
+                let count = rest
+                    .strip_suffix(" earlier entries elided")
+                    .and_then(|n| n.parse().ok())
+                    .filter(|_| history.elided == 0 && history.is_empty())
+                    .ok_or((i, "unexpected elided entries line"))?;
+                history.elided = count;
+            } else {
+                // A continuation line of a multi-line message
+                let last = history.events.last_mut().ok_or((i, "unexpected line"))?;
+                let prefix_len = 2 + width;
+                let continuation = if line.is_empty() {
+                    line
+                } else if line.len() >= prefix_len
+                    && line.as_bytes()[..prefix_len].iter().all(|&b| b == b' ')
+                {
+                    &line[prefix_len..]
+                } else {
+                    return Err((i, "unexpected line"));
+                };
+                let mut message = last.message.to_string();
+                message.push('\n');
+                message.push_str(continuation);
+                last.message = Cow::Owned(message);
+            }
+        }
+        Ok(history)
+    }
+
+    /// Formats this history with the annotations that depend on other events in the history
+    /// (e.g., the elapsed time since the first event).
+    pub(crate) fn fmt_locations(
+        &self,
+        f: &mut fmt::Formatter,
+        style: Style,
+        options: &DisplayOptions,
+    ) -> fmt::Result {
+        style.paint(f, Style::HEADER, &options.header)?;
+        writeln!(f)?;
+        if options.reversed {
+            let len = self.events.len();
+            for i in (0..len).rev() {
+                self.fmt_location(f, style, options, i, len - 1 - i)?;
+            }
+            self.fmt_elided(f, &options.indent)?;
+        } else {
+            self.fmt_elided(f, &options.indent)?;
+            for i in 0..self.events.len() {
+                self.fmt_location(f, style, options, i, self.elided + i)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(
+        not(any(feature = "time", feature = "thread")),
+        allow(unused_variables)
+    )]
+    fn fmt_location(
+        &self,
+        f: &mut fmt::Formatter,
+        style: Style,
+        options: &DisplayOptions,
+        i: usize,
+        index: usize,
+    ) -> fmt::Result {
+        let events = self.events();
+        let e = &events[i];
+        f.write_str(&options.indent)?;
+        let mut width = 0;
+        if options.numbered {
+            write!(f, "[{}] ", index)?;
+            width = index_width(index);
+        }
+        e.fmt_with(f, style, (&options.indent, width), |f| {
+            #[cfg(feature = "time")]
+            {
+                let elapsed = e
+                    .timestamp()
+                    .and_then(|t| events[0].timestamp().map(|t0| (t, t0)))
+                    .and_then(|(t, t0)| t.duration_since(t0).ok());
+                if let (true, Some(elapsed)) = (i > 0, elapsed) {
+                    if elapsed.as_millis() > 0 {
+                        write!(f, " (+{}ms)", elapsed.as_millis())?;
+                    }
+                }
+            }
+            #[cfg(feature = "thread")]
+            {
+                if i == 0 || !e.is_same_thread(&events[i - 1]) {
+                    e.fmt_thread(f)?;
+                }
+            }
+            #[cfg(feature = "tracing")]
+            {
+                if let Some(name) = e.span_name() {
+                    write!(f, " {{span: {}}}", name)?;
+                }
+            }
+            Ok(())
+        })?;
+        writeln!(f)?;
+        Ok(())
+    }
+}
+
+/// The location of interest in source code files.
+///
+/// Typically this is created in the macros which defined in this crate.
+///
+/// The alternate form of `Display` (i.e., `{:#}`) also shows the module path of the location
+/// (unless it is empty), and the sequence number if the `seq` feature is enabled
+/// (see [`seq`](#method.seq)).
+/// The default form does not show them, so that the existing `Display` output of histories is kept unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::Location;
+///
+/// let location = Location::new("foo::bar", "src/bar.rs", 10, "Hello");
+/// assert_eq!(format!("{}", location), "at src/bar.rs:10 -- Hello");
+/// # #[cfg(not(feature = "seq"))]
+/// assert_eq!(format!("{:#}", location), "at src/bar.rs:10 (foo::bar) -- Hello");
+/// # #[cfg(feature = "seq")]
+/// # assert_eq!(
+/// #     format!("{:#}", location),
+/// #     format!("at src/bar.rs:10 (foo::bar) {{seq: {}}} -- Hello", location.seq())
+/// # );
+/// ```
+///
+/// If the `serialize` feature is enabled, a location is serialized as an object
+/// which has the following fields:
+///
+/// - `file`: the file name (string)
+/// - `line`: the line number (integer)
+/// - `column`: the column number (integer, omitted if unknown)
+/// - `module_path`: the module path (string, omitted if empty)
+/// - `message`: the message (string)
+/// - `function`: the function name (string, omitted if unknown)
+/// - `repeat_count`: the repeat count (integer, omitted if `1`)
+/// - `tag`: the tag (string, omitted if not given)
+/// - `seq`: the sequence number (integer, only if the `seq` feature is enabled)
+/// - `timestamp`: the creation time as an RFC 3339 string in UTC
+///   (e.g., `"2023-11-14T22:13:20.5Z"`, only if the `time` feature is enabled)
+/// - `thread_name`: the name of the creating thread (only if the `thread` feature is enabled)
+/// - `span_name` and `span_target`: the metadata of the current `tracing` span
+///   (only if the `tracing` feature is enabled)
+///
+/// Locations are compared and hashed by their code positions (i.e., file, line and column) only.
+/// The other fields such as messages and timestamps are ignored.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use trackable::Location;
+///
+/// let a = Location::new("foo", "src/foo.rs", 10, "Hello");
+/// let b = Location::new("foo", "src/foo.rs", 10, "World");
+/// assert_eq!(a, b);
+/// assert_eq!(vec![a, b].into_iter().collect::<HashSet<_>>().len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Location {
+    module_path: Cow<'static, str>,
+    file: Cow<'static, str>,
+    line: u32,
+    column: u32,
+    message: Cow<'static, str>,
+    function: Option<Cow<'static, str>>,
+    repeat_count: u32,
+    tag: Option<Cow<'static, str>>,
+    #[cfg(feature = "seq")]
+    seq: u64,
+    #[cfg(feature = "time")]
+    timestamp: Option<SystemTime>,
+    #[cfg(feature = "thread")]
+    thread_id: Option<ThreadId>,
+    #[cfg(feature = "thread")]
+    thread_name: Option<String>,
+    #[cfg(feature = "tracing")]
+    span_name: Option<Cow<'static, str>>,
+    #[cfg(feature = "tracing")]
+    span_target: Option<Cow<'static, str>>,
+}
+impl Location {
+    /// Makes a new `Location` instance.
+    ///
+    /// This is the constructor used by [track!](macro.track.html) and the other tracking macros,
+    /// and is a stable public API, so that downstream crates can also build history entries
+    /// without the macros (e.g., in tests or to reconstruct errors from other representations).
+    /// Pass an empty `module_path` or `message` if they are unknown or unnecessary.
+    ///
+    /// The column of the resulting location is `0` (i.e., unknown).
+    /// Use [`with_column`](#method.with_column) to set it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let location = Location::new(module_path!(), file!(), line!(), "Hello".to_string());
+    /// assert_eq!(location.message(), "Hello");
+    ///
+    /// let mut history = History::new();
+    /// history.add(Location::new("", "src/foo.rs", 10, ""));
+    /// history.add(Location::new("", "src/foo.rs", 20, "bar"));
+    /// assert_eq!(history.to_string(), "HISTORY:\n  [0] at src/foo.rs:10\n  [1] at src/foo.rs:20 -- bar\n");
+    /// ```
+    #[inline]
+    pub fn new<M, F, T>(module_path: M, file: F, line: u32, message: T) -> Self
+    where
+        M: Into<Cow<'static, str>>,
+        F: Into<Cow<'static, str>>,
+        T: Into<Cow<'static, str>>,
+    {
+        #[cfg(feature = "tracing")]
+        let metadata = tracing::Span::current().metadata();
+        Location {
+            module_path: module_path.into(),
+            file: file.into(),
+            line,
+            column: 0,
+            message: message.into(),
+            function: None,
+            repeat_count: 1,
+            tag: None,
+            #[cfg(feature = "seq")]
+            seq: next_seq(),
+            #[cfg(feature = "time")]
+            timestamp: Some(SystemTime::now()),
+            #[cfg(feature = "thread")]
+            thread_id: Some(thread::current().id()),
+            #[cfg(feature = "thread")]
+            thread_name: thread::current().name().map(ToOwned::to_owned),
+            #[cfg(feature = "tracing")]
+            span_name: metadata.map(|m| Cow::Borrowed(m.name())),
+            #[cfg(feature = "tracing")]
+            span_target: metadata.map(|m| Cow::Borrowed(m.target())),
+        }
+    }
+
+    fn from_caller<T>(caller: &panic::Location<'static>, message: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        Location::new("", caller.file(), caller.line(), message).with_column(caller.column())
+    }
+
+    /// Sets the column of this location.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new(module_path!(), "src/foo.rs", 10, "").with_column(5);
+    /// assert_eq!(location.column(), 5);
+    /// assert_eq!(location.to_string(), "at src/foo.rs:10:5");
+    /// ```
+    #[inline]
+    pub fn with_column(mut self, column: u32) -> Self {
+        self.column = column;
+        self
+    }
+
+    /// Sets the name of the function where this location is in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new(module_path!(), "src/foo.rs", 10, "").with_function("handle_request");
+    /// assert_eq!(location.function(), Some("handle_request"));
+    /// assert_eq!(location.to_string(), "at src/foo.rs:10 [handle_request]");
+    /// ```
+    #[inline]
+    pub fn with_function<T>(mut self, function: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.function = Some(function.into());
+        self
+    }
+
+    /// Sets the tag that categorizes this location (e.g., `"retry"` or `"fallback"`).
+    ///
+    /// Tagged locations can be picked out by
+    /// [`History::entries_with_tag`](struct.History.html#method.entries_with_tag).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new(module_path!(), "src/foo.rs", 10, "").with_tag("retry");
+    /// assert_eq!(location.tag(), Some("retry"));
+    /// assert_eq!(location.to_string(), "at src/foo.rs:10 #retry");
+    /// ```
+    #[inline]
+    pub fn with_tag(mut self, tag: &'static str) -> Self {
+        self.tag = Some(Cow::Borrowed(tag));
+        self
+    }
+
+    /// Gets the tag of this location.
+    ///
+    /// This is `Some` only if the tag is given explicitly
+    /// (e.g., `track!(target, tag = "retry")`).
+    #[inline]
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_ref().map(|t| t.as_ref())
+    }
+
+    /// Gets the crate name of this location.
+    #[inline]
+    pub fn crate_name(&self) -> &str {
+        if let Some(module_path_end) = self.module_path.find(':') {
+            &self.module_path[..module_path_end]
+        } else {
+            self.module_path.as_ref()
+        }
+    }
+
+    /// Gets the module path of this location.
+    ///
+    /// The [track!](macro.track.html) macro captures the result of `module_path!()` at the calling site.
+    /// An empty string is returned if the module path is unknown
+    /// (e.g., the location was created by [`Location::new`](#method.new) with an empty `module_path`).
+    ///
+    /// Note that this returns `&str` rather than `Option<&str>` for compatibility,
+    /// since this method has been a part of the public API with that signature.
+    #[inline]
+    pub fn module_path(&self) -> &str {
+        self.module_path.as_ref()
+    }
+
+    /// Gets the file name of this location.
+    #[inline]
+    pub fn file(&self) -> &str {
+        self.file.as_ref()
+    }
+
+    /// Gets the line of this location.
+    #[inline]
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Gets the column of this location.
+    ///
+    /// `0` means that the column is unknown.
+    #[inline]
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// Gets the name of the function where this location is in.
+    ///
+    /// This is `Some` only if the name is given explicitly
+    /// (e.g., `track!(target, fn = "handle_request")`).
+    #[inline]
+    pub fn function(&self) -> Option<&str> {
+        self.function.as_deref()
+    }
+
+    /// Gets the number of the times this location was tracked in succession.
+    ///
+    /// This is greater than `1` only if the location was added by
+    /// [`History::add_dedup`](struct.History.html#method.add_dedup)
+    /// (e.g., via [`track_dedup!`](macro.track_dedup.html) macro).
+    /// If so, it is shown in the `Display` form (e.g., `at src/foo.rs:42 (x17)`).
+    #[inline]
+    pub fn repeat_count(&self) -> u32 {
+        self.repeat_count
+    }
+
+    fn is_same_site(&self, other: &Location) -> bool {
+        self.file == other.file
+            && self.line == other.line
+            && self.column == other.column
+            && self.module_path == other.module_path
+            && self.function == other.function
+            && self.tag == other.tag
+            && self.message == other.message
+    }
+
+    /// Gets the message left at this location.
+    #[inline]
+    pub fn message(&self) -> &str {
+        self.message.as_ref()
+    }
+
+    /// Gets the sequence number of this location.
+    ///
+    /// If the `seq` feature is enabled, [`Location::new`](#method.new) takes the number from
+    /// a crate-global counter, so the numbers of locations are unique within a process
+    /// and increase in the order in which the locations are created (even across threads).
+    /// A repeated entry made by [`History::add_dedup`](struct.History.html#method.add_dedup)
+    /// keeps the number of its first location.
+    /// On targets without 64-bit atomic operations, the counter is pointer-sized,
+    /// so it wraps around after `usize::MAX`.
+    ///
+    /// The number is shown in the alternate `Display` form of this location (e.g., `{seq: 17}`),
+    /// but not in the default one.
+    /// The number of a `Location` deserialized from data that has no sequence number is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let a = Location::new("", "src/foo.rs", 10, "");
+    /// let b = Location::new("", "src/foo.rs", 20, "");
+    /// assert!(a.seq() < b.seq());
+    /// ```
+    #[cfg(feature = "seq")]
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Gets the time when this location was created.
+    ///
+    /// If the `time` feature is enabled, [`Location::new`](#method.new) records the current time.
+    /// The timestamp of a `Location` deserialized from data that has no timestamp is `None`.
+    ///
+    /// In the `Display` form of a `TrackableError`, the time elapsed since the first event
+    /// is appended to each event (e.g., `at src/foo.rs:30 (+12ms)`), unless it is less than a millisecond.
+    #[cfg(feature = "time")]
+    #[inline]
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// Gets the name of the thread in which this location was created.
+    ///
+    /// If the `thread` feature is enabled, [`Location::new`](#method.new) records
+    /// the name of the current thread.
+    /// `None` is returned if the thread is unnamed.
+    ///
+    /// In the `Display` form of a `TrackableError`, the first event and every event tracked in a thread
+    /// different from the one of the previous event are annotated with the thread
+    /// (e.g., `at src/worker.rs:88 {thread: worker-3}`).
+    #[cfg(feature = "thread")]
+    #[inline]
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread_name.as_ref().map(|s| s.as_ref())
+    }
+
+    /// Gets the identifier of the thread in which this location was created.
+    ///
+    /// Identifiers are not serialized, so `None` is returned for deserialized locations.
+    #[cfg(feature = "thread")]
+    #[inline]
+    pub fn thread_id(&self) -> Option<ThreadId> {
+        self.thread_id
+    }
+
+    /// Gets the name of the `tracing` span that was current when this location was created.
+    ///
+    /// If the `tracing` feature is enabled, [`Location::new`](#method.new) records
+    /// the metadata of the current span.
+    /// `None` is returned if there was no (enabled) span.
+    ///
+    /// In the `Display` form of a `TrackableError`, an event is annotated with its span name
+    /// (e.g., `at src/foo.rs:42 {span: handle_request}`).
+    #[cfg(feature = "tracing")]
+    #[inline]
+    pub fn span_name(&self) -> Option<&str> {
+        self.span_name.as_ref().map(|s| s.as_ref())
+    }
+
+    /// Gets the target of the `tracing` span that was current when this location was created.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    pub fn span_target(&self) -> Option<&str> {
+        self.span_target.as_ref().map(|s| s.as_ref())
+    }
+
+    #[cfg(feature = "thread")]
+    fn is_same_thread(&self, other: &Location) -> bool {
+        match (self.thread_id, other.thread_id) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.thread_name == other.thread_name,
+        }
+    }
+
+    #[cfg(feature = "thread")]
+    fn fmt_thread(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.thread_name(), self.thread_id) {
+            (Some(name), _) => write!(f, " {{thread: {}}}", name),
+            (None, Some(id)) => write!(f, " {{thread: {:?}}}", id),
+            (None, None) => Ok(()),
+        }
+    }
+
+    /// Parses the first line of the `Display` form of a location (without the leading `at `).
+    ///
+    /// Tags and annotations (e.g., the thread name) are not supported.
+    /// The module path of the resulting location is empty as it is not shown in the `Display` form.
+    fn parse_entry(s: &str) -> Option<Location> {
+        let (s, message) = s.split_once(" -- ").unwrap_or((s, ""));
+        let (s, repeat_count) = match s.strip_suffix(')').and_then(|s| s.rsplit_once(" (x")) {
+            Some((s, n)) => (s, n.parse().ok()?),
+            None => (s, 1),
+        };
+        let (s, function) = match s.strip_suffix(']').and_then(|s| s.rsplit_once(" [")) {
+            Some((s, function)) => (s, Some(function)),
+            None => (s, None),
+        };
+        let (rest, last) = s.rsplit_once(':')?;
+        let last = last.parse().ok()?;
+        let (file, line, column) = match rest
+            .rsplit_once(':')
+            .and_then(|(file, line)| line.parse().ok().map(|line| (file, line)))
+        {
+            Some((file, line)) => (file, line, last),
+            None => (rest, last, 0),
+        };
+        let mut location =
+            Location::new("", String::from(file), line, String::from(message)).with_column(column);
+        if let Some(function) = function {
+            location = location.with_function(String::from(function));
+        }
+        location.repeat_count = repeat_count;
+        Some(location)
+    }
+
+    fn fmt_with<F>(
+        &self,
+        f: &mut fmt::Formatter,
+        style: Style,
+        indent: (&str, usize),
+        annotate: F,
+    ) -> fmt::Result
+    where
+        F: FnOnce(&mut fmt::Formatter) -> fmt::Result,
+    {
+        write!(f, "at {}:{}", self.file(), self.line())?;
+        if self.column != 0 {
+            write!(f, ":{}", self.column)?;
+        }
+        if let Some(function) = self.function() {
+            write!(f, " [{}]", function)?;
+        }
+        if f.alternate() && !self.module_path().is_empty() {
+            write!(f, " ({})", self.module_path())?;
+        }
+        #[cfg(feature = "seq")]
+        {
+            if f.alternate() {
+                write!(f, " {{seq: {}}}", self.seq)?;
+            }
+        }
+        if self.repeat_count > 1 {
+            write!(f, " (x{})", self.repeat_count)?;
+        }
+        if let Some(tag) = self.tag() {
+            write!(f, " #{}", tag)?;
+        }
+        annotate(f)?;
+        if !self.message().is_empty() {
+            write!(f, " ")?;
+            let message = Indented(self.message(), indent.0, indent.1);
+            style.paint(f, Style::MESSAGE, format_args!("-- {}", message))?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, Style::default(), ("", 0), |_| Ok(()))
+    }
+}
+impl PartialEq for Location {
+    fn eq(&self, other: &Self) -> bool {
+        self.file == other.file && self.line == other.line && self.column == other.column
+    }
+}
+impl Eq for Location {}
+impl Hash for Location {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.file.hash(state);
+        self.line.hash(state);
+        self.column.hash(state);
+    }
+}
+
+/// Options for customizing the layout of the `Display` form of a [`TrackableError`].
+///
+/// The default options produce the same output as the `Display` implementation of `TrackableError`.
+///
+/// See [`TrackableError::display_with`](error/struct.TrackableError.html#method.display_with).
+///
+/// [`TrackableError`]: error/struct.TrackableError.html
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    indent: Cow<'static, str>,
+    header: Cow<'static, str>,
+    numbered: bool,
+    reversed: bool,
+}
+impl DisplayOptions {
+    /// Makes a new `DisplayOptions` instance with the default settings.
+    pub fn new() -> Self {
+        DisplayOptions {
+            indent: Cow::Borrowed("  "),
+            header: Cow::Borrowed("HISTORY:"),
+            numbered: true,
+            reversed: false,
+        }
+    }
+
+    /// Sets the string used to indent entries (e.g., history entries and causes).
+    ///
+    /// The default value is two spaces.
+    pub fn with_indent<T>(mut self, indent: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.indent = indent.into();
+        self
+    }
+
+    /// Sets the header line of the history section.
+    ///
+    /// The default value is `"HISTORY:"`.
+    pub fn with_header<T>(mut self, header: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.header = header.into();
+        self
+    }
+
+    /// Sets whether history entries are prefixed by their indices (e.g., `[0]`).
+    ///
+    /// The default value is `true`.
+    pub fn with_numbering(mut self, numbered: bool) -> Self {
+        self.numbered = numbered;
+        self
+    }
+
+    /// Sets whether history entries are shown from the most recent one.
+    ///
+    /// If this is `true`, the entries are numbered from the most recent one
+    /// and the elided entries (if any) are noted at the end.
+    ///
+    /// The default value is `false`.
+    pub fn with_reversed(mut self, reversed: bool) -> Self {
+        self.reversed = reversed;
+        self
+    }
+
+    /// Returns the string used to indent entries.
+    pub fn indent(&self) -> &str {
+        &self.indent
+    }
+
+    /// Returns the header line of the history section.
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// Returns `true` if history entries are prefixed by their indices, otherwise `false`.
+    pub fn numbered(&self) -> bool {
+        self.numbered
+    }
+
+    /// Returns `true` if history entries are shown from the most recent one, otherwise `false`.
+    pub fn reversed(&self) -> bool {
+        self.reversed
+    }
+}
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions::new()
+    }
+}
+
+/// Displays a value with indenting its continuation lines (if any)
+/// by the given prefix followed by the given number of spaces.
+///
+/// This keeps multi-line history entries aligned under the first line.
+struct Indented<'a, T>(T, &'a str, usize);
+impl<'a, T: fmt::Display> fmt::Display for Indented<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use std::fmt::Write;
+
+        let alternate = f.alternate();
+        let mut writer = IndentWriter {
+            inner: f,
+            prefix: self.1,
+            width: self.2,
+            line_start: false,
+        };
+        if alternate {
+            write!(writer, "{:#}", self.0)
+        } else {
+            write!(writer, "{}", self.0)
+        }
+    }
+}
+
+struct IndentWriter<'a, 'b: 'a, 'c> {
+    inner: &'a mut fmt::Formatter<'b>,
+    prefix: &'c str,
+    width: usize,
+    line_start: bool,
+}
+impl<'a, 'b: 'a, 'c> fmt::Write for IndentWriter<'a, 'b, 'c> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.inner.write_str("\n")?;
+                self.line_start = true;
+            }
+            if !line.is_empty() {
+                if self.line_start {
+                    write!(self.inner, "{}{:2$}", self.prefix, "", self.width)?;
+                    self.line_start = false;
+                }
+                self.inner.write_str(line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "seq")]
+fn next_seq() -> u64 {
+    #[cfg(target_has_atomic = "64")]
+    {
+        static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+        NEXT_SEQ.fetch_add(1, Ordering::Relaxed)
+    }
+    // Targets without 64-bit atomics (e.g., some 32-bit ones) fall back to a pointer-sized counter
+    #[cfg(not(target_has_atomic = "64"))]
+    {
+        static NEXT_SEQ: AtomicUsize = AtomicUsize::new(0);
+        NEXT_SEQ.fetch_add(1, Ordering::Relaxed) as u64
+    }
+}
+
+/// Returns the width of `[{index}] `.
+fn index_width(index: usize) -> usize {
+    index.checked_ilog10().unwrap_or(0) as usize + 4
+}
+
+/// Text style used in the `Display` implementations of this crate.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Style {
+    colored: bool,
+}
+impl Style {
+    pub(crate) const KIND: &'static str = "1;31";
+    pub(crate) const HEADER: &'static str = "1";
+    pub(crate) const MESSAGE: &'static str = "36";
+
+    #[cfg(feature = "color")]
+    pub(crate) fn colored() -> Self {
+        Style { colored: true }
+    }
+
+    pub(crate) fn begin(self, f: &mut fmt::Formatter, code: &str) -> fmt::Result {
+        if self.colored {
+            write!(f, "\x1b[{}m", code)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn end(self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.colored {
+            write!(f, "\x1b[0m")?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn paint<T: fmt::Display>(
+        self,
+        f: &mut fmt::Formatter,
+        code: &str,
+        value: T,
+    ) -> fmt::Result {
+        self.begin(f, code)?;
+        write!(f, "{}", value)?;
+        self.end(f)
+    }
+}
+
+#[cfg(feature = "serialize")]
+mod impl_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::borrow::Cow;
+    #[cfg(feature = "time")]
+    use std::time::SystemTime;
+
+    use super::{History, Location};
+    #[cfg(not(feature = "std"))]
+    use prelude::*;
+
+    impl<Event: Serialize> Serialize for History<Event> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.events.serialize(serializer)
+        }
+    }
+    impl<'de, Event: Deserialize<'de>> Deserialize<'de> for History<Event> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let events = super::Events::deserialize(deserializer)?;
+            Ok(History {
+                events,
+                max_len: usize::MAX,
+                elided: 0,
+            })
+        }
+    }
+
+    #[derive(Serialize)]
+    struct LocationRef<'a> {
+        file: &'a str,
+        line: u32,
+        #[serde(skip_serializing_if = "is_zero")]
+        column: u32,
+        #[serde(skip_serializing_if = "str::is_empty")]
+        module_path: &'a str,
+        message: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        function: Option<&'a str>,
+        #[serde(skip_serializing_if = "is_one")]
+        repeat_count: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<&'a str>,
+        #[cfg(feature = "seq")]
+        seq: u64,
+        #[cfg(feature = "time")]
+        #[serde(skip_serializing_if = "Option::is_none", with = "rfc3339")]
+        timestamp: Option<SystemTime>,
+        #[cfg(feature = "thread")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_name: Option<&'a str>,
+        #[cfg(feature = "tracing")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span_name: Option<&'a str>,
+        #[cfg(feature = "tracing")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span_target: Option<&'a str>,
+    }
+
+    #[derive(Deserialize)]
+    struct LocationOwned {
+        file: String,
+        line: u32,
+        #[serde(default)]
+        column: u32,
+        #[serde(default)]
+        module_path: String,
+        #[serde(default)]
+        message: String,
+        #[serde(default)]
+        function: Option<String>,
+        #[serde(default = "one")]
+        repeat_count: u32,
+        #[serde(default)]
+        tag: Option<String>,
+        #[cfg(feature = "seq")]
+        #[serde(default)]
+        seq: u64,
+        #[cfg(feature = "time")]
+        #[serde(default, with = "rfc3339")]
+        timestamp: Option<SystemTime>,
+        #[cfg(feature = "thread")]
+        #[serde(default)]
+        thread_name: Option<String>,
+        #[cfg(feature = "tracing")]
+        #[serde(default)]
+        span_name: Option<String>,
+        #[cfg(feature = "tracing")]
+        #[serde(default)]
+        span_target: Option<String>,
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn is_zero(n: &u32) -> bool {
+        *n == 0
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn is_one(n: &u32) -> bool {
+        *n == 1
+    }
+
+    fn one() -> u32 {
+        1
+    }
+
+    /// (De)serialization of timestamps as RFC 3339 strings (e.g., `"2023-11-14T22:13:20.5Z"`).
+    #[cfg(feature = "time")]
+    mod rfc3339 {
+        use serde::de::Error;
+        use serde::{Deserialize, Deserializer, Serializer};
+        use std::fmt;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        const SECS_PER_DAY: i64 = 86_400;
+
+        pub fn serialize<S>(t: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match *t {
+                Some(t) => serializer.collect_str(&Rfc3339(t)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<SystemTime>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            // The `Legacy` form is the one used by older versions of this crate.
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum Timestamp {
+                Rfc3339(String),
+                Legacy(SystemTime),
+            }
+
+            match Option::<Timestamp>::deserialize(deserializer)? {
+                None => Ok(None),
+                Some(Timestamp::Legacy(t)) => Ok(Some(t)),
+                Some(Timestamp::Rfc3339(s)) => parse(&s).map(Some).ok_or_else(|| {
+                    D::Error::custom(format!("invalid RFC 3339 timestamp: {:?}", s))
+                }),
+            }
+        }
+
+        struct Rfc3339(SystemTime);
+        impl fmt::Display for Rfc3339 {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let (secs, nanos) = match self.0.duration_since(UNIX_EPOCH) {
+                    Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+                    Err(e) => {
+                        let d = e.duration();
+                        let secs = -(d.as_secs() as i64);
+                        match d.subsec_nanos() {
+                            0 => (secs, 0),
+                            n => (secs - 1, 1_000_000_000 - n),
+                        }
+                    }
+                };
+                let (year, month, day) = civil_from_days(secs.div_euclid(SECS_PER_DAY));
+                let secs_of_day = secs.rem_euclid(SECS_PER_DAY);
+                write!(
+                    f,
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    year,
+                    month,
+                    day,
+                    secs_of_day / 3600,
+                    secs_of_day / 60 % 60,
+                    secs_of_day % 60
+                )?;
+                if nanos > 0 {
+                    let fraction = format!("{:09}", nanos);
+                    write!(f, ".{}", fraction.trim_end_matches('0'))?;
+                }
+                f.write_str("Z")
+            }
+        }
+
+        fn parse(s: &str) -> Option<SystemTime> {
+            fn number(s: &str, range: (i64, i64)) -> Option<i64> {
+                if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                s.parse().ok().filter(|n| range.0 <= *n && *n <= range.1)
+            }
+
+            // The fields are sliced by byte offsets below
+            if !s.is_ascii() {
+                return None;
+            }
+            let b = s.as_bytes();
+            if b.len() < 20
+                || b[4] != b'-'
+                || b[7] != b'-'
+                || !(b[10] == b'T' || b[10] == b't' || b[10] == b' ')
+                || b[13] != b':'
+                || b[16] != b':'
+            {
+                return None;
+            }
+            let year = number(&s[0..4], (0, 9999))?;
+            let month = number(&s[5..7], (1, 12))?;
+            let day = number(&s[8..10], (1, 31))?;
+            let hour = number(&s[11..13], (0, 23))?;
+            let minute = number(&s[14..16], (0, 59))?;
+            let second = number(&s[17..19], (0, 60))?;
+
+            let mut rest = &s[19..];
+            let mut nanos = 0;
+            if rest.starts_with('.') {
+                let end = rest[1..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .map_or(rest.len(), |i| i + 1);
+                let digits = &rest[1..end];
+                if digits.is_empty() {
+                    return None;
+                }
+                for (i, d) in digits.bytes().take(9).enumerate() {
+                    nanos += u32::from(d - b'0') * 10u32.pow(8 - i as u32);
+                }
+                rest = &rest[end..];
+            }
+            let offset = match rest {
+                "Z" | "z" => 0,
+                _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+                    let sign = match rest.as_bytes()[0] {
+                        b'+' => 1,
+                        b'-' => -1,
+                        _ => return None,
+                    };
+                    let hours = number(&rest[1..3], (0, 23))?;
+                    let minutes = number(&rest[4..6], (0, 59))?;
+                    sign * (hours * 3600 + minutes * 60)
+                }
+                _ => return None,
+            };
+
+            let secs = days_from_civil(year, month, day) * SECS_PER_DAY
+                + hour * 3600
+                + minute * 60
+                + second
+                - offset;
+            if secs >= 0 {
+                UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+            } else {
+                UNIX_EPOCH
+                    .checked_sub(Duration::from_secs(-secs as u64))
+                    .and_then(|t| t.checked_add(Duration::new(0, nanos)))
+            }
+        }
+
+        // See http://howardhinnant.github.io/date_algorithms.html
+        fn civil_from_days(days: i64) -> (i64, i64, i64) {
+            let z = days + 719_468;
+            let era = z.div_euclid(146_097);
+            let doe = z.rem_euclid(146_097);
+            let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+            let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+            let mp = (5 * doy + 2) / 153;
+            let day = doy - (153 * mp + 2) / 5 + 1;
+            let month = if mp < 10 { mp + 3 } else { mp - 9 };
+            let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+            (year, month, day)
+        }
+
+        fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+            let year = if month <= 2 { year - 1 } else { year };
+            let era = year.div_euclid(400);
+            let yoe = year.rem_euclid(400);
+            let mp = if month > 2 { month - 3 } else { month + 9 };
+            let doy = (153 * mp + 2) / 5 + day - 1;
+            let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+            era * 146_097 + doe - 719_468
+        }
+    }
+
+    impl Serialize for Location {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            LocationRef {
+                file: &self.file,
+                line: self.line,
+                column: self.column,
+                module_path: &self.module_path,
+                message: &self.message,
+                function: self.function(),
+                repeat_count: self.repeat_count,
+                tag: self.tag(),
+                #[cfg(feature = "seq")]
+                seq: self.seq,
+                #[cfg(feature = "time")]
+                timestamp: self.timestamp,
+                #[cfg(feature = "thread")]
+                thread_name: self.thread_name(),
+                #[cfg(feature = "tracing")]
+                span_name: self.span_name(),
+                #[cfg(feature = "tracing")]
+                span_target: self.span_target(),
+            }
+            .serialize(serializer)
+        }
+    }
+    impl<'de> Deserialize<'de> for Location {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let l = LocationOwned::deserialize(deserializer)?;
+            Ok(Location {
+                module_path: Cow::Owned(l.module_path),
+                file: Cow::Owned(l.file),
+                line: l.line,
+                column: l.column,
+                message: Cow::Owned(l.message),
+                function: l.function.map(Cow::Owned),
+                repeat_count: l.repeat_count,
+                tag: l.tag.map(Cow::Owned),
+                #[cfg(feature = "seq")]
+                seq: l.seq,
+                #[cfg(feature = "time")]
+                timestamp: l.timestamp,
+                #[cfg(feature = "thread")]
+                thread_id: None,
+                #[cfg(feature = "thread")]
+                thread_name: l.thread_name,
+                #[cfg(feature = "tracing")]
+                span_name: l.span_name.map(Cow::Owned),
+                #[cfg(feature = "tracing")]
+                span_target: l.span_target.map(Cow::Owned),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::{ErrorKindExt, Failed, Failure};
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+
+    /// Removes the thread annotations (e.g., ` {thread: main}`) from `s`,
+    /// so that assertions on `Display` strings hold regardless of the `thread` feature.
+    #[cfg(not(feature = "disable_tracking"))]
+    pub fn strip_thread(s: &str) -> String {
+        let mut s = s.to_owned();
+        while let Some(start) = s.find(" {thread: ") {
+            let end = start + s[start..].find('}').expect("unterminated annotation") + 1;
+            s.replace_range(start..end, "");
+        }
+        s
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn it_works() {
+        fn foo() -> Result<(), Failure> {
+            track!(std::fs::File::open("/path/to/non_existent_file")
+                .map_err(|e| Failure::from_error(format!("{:?}", e.kind()))))?;
+            Ok(())
+        }
+        fn bar() -> Result<(), Failure> {
+            track!(foo())?;
+            Ok(())
+        }
+        fn baz() -> Result<(), Failure> {
+            track!(bar())?;
+            Ok(())
+        }
+
+        let result = baz();
+        assert!(result.is_err());
+
+        let error = result.err().unwrap();
+        assert_eq!(
+            strip_thread(&format!("\n{}", error).replace('\\', "/")),
+            r#"
+Failed (cause; NotFound)
+HISTORY:
+  [0] at src/lib.rs:2219:13
+  [1] at src/lib.rs:2224:13
+  [2] at src/lib.rs:2228:13
+"#
+        );
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_function_works() {
+        let e = track!(Failed.error(), fn = "handle_request");
+        let e = track!(e);
+        let events = e.history().unwrap().events();
+        assert_eq!(events[0].function(), Some("handle_request"));
+        assert_eq!(events[1].function(), None);
+
+        let s = strip_thread(&e.to_string());
+        let lines = s.lines().collect::<Vec<_>>();
+        assert!(lines[2].ends_with(" [handle_request]"));
+        assert_eq!(lines[3].matches('[').count(), 1);
+
+        #[cfg(feature = "serialize")]
+        {
+            extern crate serde_json;
+            let json = serde_json::to_value(events).unwrap();
+            assert_eq!(json[0]["function"], "handle_request");
+            assert!(json[1].get("function").is_none());
+        }
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
     #[test]
-    fn it_works() {
-        fn foo() -> Result<(), Failure> {
-            track!(std::fs::File::open("/path/to/non_existent_file")
-                .map_err(|e| Failure::from_error(format!("{:?}", e.kind()))))?;
-            Ok(())
+    fn location_column_works() {
+        let location = Location::new(module_path!(), "src/foo.rs", 27, "");
+        assert_eq!(location.column(), 0);
+        assert_eq!(location.to_string(), "at src/foo.rs:27");
+
+        let location = location.with_column(13);
+        assert_eq!(location.column(), 13);
+        assert_eq!(location.to_string(), "at src/foo.rs:27:13");
+
+        let (a, b) = (track!(Failed.error()), track!(Failed.error()));
+        let a = &a.history().unwrap().events()[0];
+        let b = &b.history().unwrap().events()[0];
+        assert_eq!(a.file(), b.file());
+        assert_eq!(a.line(), b.line());
+        assert_ne!(a.column(), b.column());
+        assert_ne!(a.to_string(), b.to_string());
+    }
+
+    #[test]
+    fn location_hash_works() {
+        use std::collections::HashSet;
+
+        let mut e = Failed.error();
+        {
+            let history = e.history_mut().unwrap();
+            history.add(Location::new("foo", "src/foo.rs", 10, "a"));
+            history.add(Location::new("foo", "src/foo.rs", 10, "b"));
+            history.add(Location::new("foo", "src/foo.rs", 10, "").with_column(5));
+            history.add(Location::new("bar", "src/bar.rs", 10, ""));
+            history.add(Location::new("foo", "src/foo.rs", 20, ""));
+            history.add(Location::new("foo", "src/foo.rs", 10, "c").with_function("foo"));
         }
-        fn bar() -> Result<(), Failure> {
-            track!(foo())?;
-            Ok(())
+        let events = e.history().unwrap().events();
+        let locations = events.iter().collect::<HashSet<_>>();
+        assert_eq!(locations.len(), 4);
+        assert!(locations.contains(&Location::new("", "src/foo.rs", 10, "")));
+        assert!(!locations.contains(&Location::new("", "src/foo.rs", 30, "")));
+        assert_eq!(events[0], events[5]);
+        assert_ne!(events[0], events[2]);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_module_path_works() {
+        mod net {
+            pub mod client {
+                use error::{ErrorKindExt, Failed, Failure};
+
+                pub fn connect() -> Failure {
+                    track!(Failure::from(Failed.error()), "connection refused")
+                }
+            }
         }
-        fn baz() -> Result<(), Failure> {
-            track!(bar())?;
-            Ok(())
+
+        let e = net::client::connect();
+        let location = &e.history().unwrap().events()[0];
+        assert_eq!(location.module_path(), "trackable::test::net::client");
+        assert_eq!(location.crate_name(), "trackable");
+
+        let line = location.line();
+        #[cfg(feature = "seq")]
+        let seq = format!(" {{seq: {}}}", location.seq());
+        #[cfg(not(feature = "seq"))]
+        let seq = "";
+        assert_eq!(
+            format!("{}", location).replace('\\', "/"),
+            format!("at src/lib.rs:{}:21 -- connection refused", line)
+        );
+        assert_eq!(
+            format!("{:#}", location).replace('\\', "/"),
+            format!(
+                "at src/lib.rs:{}:21 (trackable::test::net::client){} \
+                 -- connection refused",
+                line, seq
+            )
+        );
+        assert_eq!(
+            format!("{:#}", e.history().unwrap()).replace('\\', "/"),
+            format!(
+                "HISTORY:\n  [0] at src/lib.rs:{}:21 \
+                 (trackable::test::net::client){} -- connection refused\n",
+                line, seq
+            )
+        );
+
+        let location = Location::new("", "src/foo.rs", 27, "");
+        #[cfg(not(feature = "seq"))]
+        assert_eq!(format!("{:#}", location), "at src/foo.rs:27");
+        #[cfg(feature = "seq")]
+        assert_eq!(
+            format!("{:#}", location),
+            format!("at src/foo.rs:27 {{seq: {}}}", location.seq())
+        );
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn no_std_works() {
+        use alloc::string::ToString;
+
+        let e = track!(Failed.cause("something wrong"), "hello {}", 1);
+        let s = e.to_string();
+        assert!(s.starts_with("Failed (cause; something wrong)\nHISTORY:\n  [0] at src/lib.rs:"));
+        assert!(s.ends_with(" -- hello 1\n"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_events_works() {
+        let e = track!(Failed.error());
+        let e = track!(e, "second site");
+
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message(), "");
+        assert_eq!(events[1].message(), "second site");
+        assert_eq!(events[1].file().replace('\\', "/"), "src/lib.rs");
+        assert_eq!(events[1].line(), events[0].line() + 1);
+        assert_eq!(events[1].module_path(), module_path!());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_capacity_works() {
+        let history = History::<Location>::new();
+        #[cfg(not(feature = "smallvec"))]
+        assert_eq!(history.capacity(), 0);
+        #[cfg(feature = "smallvec")]
+        assert_eq!(history.capacity(), HISTORY_INLINE_CAPACITY);
+
+        let mut history = History::<Location>::with_capacity(10);
+        assert!(history.capacity() >= 10);
+
+        history.reserve(20);
+        assert!(history.capacity() >= 20);
+
+        let mut e = Failed.error();
+        e.history_mut().unwrap().reserve(3);
+        let capacity = e.history().unwrap().capacity();
+        let e = track!(track!(track!(e)));
+        assert_eq!(e.history().unwrap().len(), 3);
+        assert_eq!(e.history().unwrap().capacity(), capacity);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_dedup_works() {
+        let mut e = Failed.error();
+        for _ in 0..5 {
+            e = track_dedup!(e);
         }
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].repeat_count(), 5);
+        assert!(strip_thread(&e.to_string()).ends_with(" (x5)\n"));
 
-        let result = baz();
-        assert!(result.is_err());
+        let mut e = Failed.error();
+        for i in 0..3 {
+            e = track_dedup!(e, "i={}", i);
+            e = track_dedup!(e, "same");
+        }
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 6);
+        assert!(events.iter().all(|l| l.repeat_count() == 1));
 
-        let error = result.err().unwrap();
+        let mut e = Failed.error();
+        for _ in 0..3 {
+            e = track_dedup!(e, "same");
+        }
+        let e = track!(e);
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].repeat_count(), 3);
+        assert_eq!(events[1].repeat_count(), 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn trackable_result_ext_works() {
+        fn fail() -> Result<(), Failure> {
+            Err(Failed.error().into())
+        }
+
+        let line = line!() + 2;
+        let e = fail()
+            .track()
+            .track_msg(format_args!("n={}", 1))
+            .err()
+            .unwrap();
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].file(), file!());
+        assert_eq!(events[0].line(), line);
+        assert_eq!(events[0].message(), "");
+        assert_eq!(events[1].line(), line + 1);
+        assert_eq!(events[1].message(), "n=1");
+
+        // A `track!` invocation does not conflict with the extension methods
+        let e: Result<(), Failure> = track!(Err(e));
+        assert_eq!(e.err().unwrap().history().unwrap().events().len(), 3);
+
+        let ok: Result<u8, Failure> = Ok(1);
+        assert_eq!(ok.track().track_msg("unused").ok(), Some(1));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_new_works() {
+        let location = Location::new("", "src/foo.rs", 10, "");
+        assert_eq!(location.file(), "src/foo.rs");
+        assert_eq!(location.line(), 10);
+        assert_eq!(location.column(), 0);
+        assert_eq!(location.module_path(), "");
+        assert_eq!(location.message(), "");
+
+        let file = String::from("src/bar.rs");
+        let location = Location::new("bar", file, 20, format!("id={}", 3));
+        assert_eq!(location.file(), "src/bar.rs");
+        assert_eq!(location.crate_name(), "bar");
+        assert_eq!(location.message(), "id=3");
+
+        let mut e = Failed.error();
+        e.history_mut().unwrap().add(location.clone());
+        let e = track!(e);
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], location);
+        assert_eq!(
+            strip_thread(&e.to_string()).lines().nth(2),
+            Some("  [0] at src/bar.rs:20 -- id=3")
+        );
+    }
+
+    #[test]
+    fn tracked_works() {
+        #[derive(Default)]
+        struct Object {
+            history: Option<History<Location>>,
+        }
+        impl Trackable for Object {
+            type Event = Location;
+            fn history(&self) -> Option<&History<Location>> {
+                self.history.as_ref()
+            }
+            fn history_mut(&mut self) -> Option<&mut History<Location>> {
+                self.history.as_mut()
+            }
+        }
+
+        let o = Object {
+            history: Some(History::new()),
+        };
+        let o = o.tracked().tracked();
+        let line = line!() - 1;
+        let events = o.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].line(), line);
+        assert_eq!(events[0].column(), 19);
+        assert_eq!(events[1].column(), 29);
+        assert_eq!(events[0].file(), file!());
+
+        // Not being tracked
+        let o = Object::default().tracked();
+        assert!(o.history().is_none());
+        assert_eq!(o.history_len(), 0);
+    }
+
+    #[test]
+    fn try_track_works() {
+        let r: Result<(), Failure> = Err(Failed.error().into());
+        let e = try_track(r).err().unwrap();
+        let line = line!() - 1;
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].file(), file!());
+        assert_eq!(events[0].line(), line);
+        assert_eq!(events[0].column(), 17);
+
+        let r: Result<usize, Failure> = Ok(10);
+        assert_eq!(try_track(r).ok(), Some(10));
+    }
+
+    #[test]
+    fn track_here_works() {
+        #[track_caller]
+        fn helper<T: Trackable>(target: &mut T) {
+            target.track_here();
+        }
+
+        let mut e = Failed.error();
+        helper(&mut e);
+        let line = line!() - 1;
+
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].file(), file!());
+        assert_eq!(events[0].line(), line);
+        assert_eq!(events[0].column(), 9);
+        assert_eq!(events[0].module_path(), "");
+
+        let mut untracked: Option<Failure> = None;
+        untracked.track_here();
+        assert!(untracked.history().is_none());
+    }
+
+    #[test]
+    fn history_contains_works() {
+        let mut history = History::new();
+        assert!(!history.contains_message(""));
+        assert!(!history.contains_location("src/foo.rs", 10));
+
+        history.add(Location::new("foo", "src/foo.rs", 10, "hello world"));
+        history.add(Location::new("bar", "src/bar.rs", 20, ""));
+
+        assert!(history.contains_message("hello"));
+        assert!(history.contains_message("o w"));
+        assert!(!history.contains_message("bye"));
+
+        assert!(history.contains_location("src/foo.rs", 10));
+        assert!(history.contains_location("src/bar.rs", 20));
+        assert!(!history.contains_location("src/foo.rs", 20));
+        assert!(!history.contains_location("src/baz.rs", 10));
+    }
+
+    #[cfg(feature = "disable_tracking")]
+    #[test]
+    fn disable_tracking_works() {
+        let e = track!(Failed.error());
+        let e = track!(e, "foo");
+        let e = track!(e; 1, 2);
+        let e = track_dedup!(e, "bar");
+        assert!(e.in_tracking());
+        assert_eq!(e.history_len(), 0);
+        assert_eq!(e.track_count(), 0);
+
+        let e: Result<(), Failure> = Err(Failed.into());
+        assert_eq!(track!(e).unwrap_err().history_len(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn write_to_works() {
+        let mut history = History::new();
+        history.add("foo\nbar");
+        history.add("baz");
+        let mut buf = Vec::new();
+        history.write_to(&mut buf).unwrap();
+        assert_eq!(buf, history.to_string().into_bytes());
+
+        let e = track!(track!(Failed.cause("something wrong")), "qux");
+        let e = e.with_context("key", "value");
+        let mut buf = Vec::new();
+        e.write_to(&mut buf).unwrap();
+        assert_eq!(buf, e.to_string().into_bytes());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_entries_since_works() {
+        let e = track!(Failed.error(), "foo");
+        let checkpoint = e.history().unwrap().checkpoint();
+        assert_eq!(checkpoint, 1);
+        assert!(e.history().unwrap().entries_since(checkpoint).is_empty());
+
+        let e = track!(e, "bar");
+        let e = track!(e, "baz");
+        let history = e.history().unwrap();
+        let entries = history.entries_since(checkpoint);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].message(), "bar");
+        assert_eq!(entries[1].message(), "baz");
+        assert_eq!(history.entries_since(0).len(), 3);
+        assert!(history.entries_since(10).is_empty());
+
+        let mut history = History::new();
+        history.set_max_len(2);
+        history.add(0);
+        let checkpoint = history.checkpoint();
+        for i in 1..4 {
+            history.add(i);
+        }
+        assert_eq!(history.entries_since(checkpoint), [2, 3]);
+        assert_eq!(history.entries_since(history.checkpoint() - 1), [3]);
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_await_works() {
+        use std::future;
+        use std::sync::Arc;
+        use std::task::{Wake, Waker};
+
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+        fn poll<F: Future>(future: F) -> Poll<F::Output> {
+            let waker = Waker::from(Arc::new(NoopWaker));
+            let mut cx = Context::from_waker(&waker);
+            let mut future = Box::pin(future);
+            future.as_mut().poll(&mut cx)
+        }
+
+        let r: Result<(), Failure> = Err(Failed.into());
+        let Poll::Ready(r) = poll(track_await!(future::ready(r))) else {
+            panic!()
+        };
+        let line = line!() - 3;
+        let e = r.unwrap_err();
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].line(), line);
+        assert_eq!(events[0].column(), 35);
+        assert_eq!(events[0].module_path(), module_path!());
+
+        let r: Result<(), Failure> = Err(Failed.into());
+        let Poll::Ready(r) = poll(track_await!(future::ready(r), "id={}", 10)) else {
+            panic!()
+        };
+        assert_eq!(
+            r.unwrap_err().history().unwrap().events()[0].message(),
+            "id=10"
+        );
+
+        let r: Result<u32, Failure> = Ok(10);
+        let Poll::Ready(r) = poll(track_await!(future::ready(r))) else {
+            panic!()
+        };
+        assert_eq!(r.ok(), Some(10));
+
+        let r = poll(track_await!(future::pending::<Result<(), Failure>>()));
+        assert!(r.is_pending());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn result_trackable_works() {
+        fn history_len<T: Trackable>(target: &T) -> Option<usize> {
+            target.history().map(History::len)
+        }
+
+        let ok: Result<(), Failure> = Ok(());
+        assert!(ok.history().is_none());
+        assert!(!ok.in_tracking());
+        assert_eq!(history_len(&ok), None);
+        let ok = track!(ok);
+        assert_eq!(history_len(&ok), None);
+
+        let err: Result<(), Failure> = Err(track!(Failed.into()));
+        assert_eq!(history_len(&err), Some(1));
+        let mut err = track!(err);
+        assert_eq!(
+            err.history().map(History::len),
+            err.as_ref().err().unwrap().history().map(History::len)
+        );
+        err.history_mut()
+            .unwrap()
+            .add(Location::new("", "a.rs", 1, ""));
+        assert_eq!(err.unwrap_err().history().unwrap().len(), 3);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_retain_works() {
+        let e = track!(Failed.error());
+        let e = track!(e, "foo");
+        let e = track!(e);
+        let mut e = track!(e, "bar");
+        e.history_mut().unwrap().retain(|l| !l.message().is_empty());
+
+        let history = e.history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history.events().iter().all(|l| !l.message().is_empty()));
+        let s = e.to_string();
+        assert!(s.contains("\n  [0] at "));
+        assert!(s.contains(" -- foo\n  [1] at "));
+        assert!(s.ends_with(" -- bar\n"));
+        assert!(!s.contains("[2]"));
+
+        let mut history = History::new();
+        history.add(0);
+        history.retain(|_| false);
+        assert!(history.is_empty());
+
+        // Entries are numbered from the elided count
+        let mut history = History::new();
+        history.set_max_len(3);
+        for i in 0..5 {
+            history.add(i);
+        }
+        history.retain(|&i| i != 3);
+        assert_eq!(history.events(), [2, 4]);
+        assert_eq!(
+            history.to_string(),
+            "HISTORY:\n  ... 2 earlier entries elided\n  [2] 2\n  [3] 4\n"
+        );
+    }
+
+    #[test]
+    fn history_insert_works() {
+        let mut history = History::new();
+        history.add(1);
+        history.insert(0, 0);
+        history.insert(2, 2);
+        assert_eq!(history.events(), [0, 1, 2]);
+
+        history.set_max_len(3);
+        history.insert(1, 3);
+        assert_eq!(history.events(), [3, 1, 2]);
+        assert_eq!(history.elided_count(), 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_clear_works() {
+        let mut history = History::new();
+        history.set_max_len(3);
+        for i in 0..5 {
+            history.add(i);
+        }
+        let capacity = history.events.capacity();
+        history.clear();
+        assert_eq!(history.len(), 0);
+        assert_eq!(history.elided_count(), 0);
+        assert_eq!(history.max_len(), Some(3));
+        assert_eq!(history.events.capacity(), capacity);
+
+        let mut e = track!(track!(Failed.error()));
+        let capacity = e.history().unwrap().events.capacity();
+        e.reset_history();
+        assert_eq!(e.history_len(), 0);
+        assert_eq!(e.history().unwrap().events.capacity(), capacity);
+        assert_eq!(e.to_string(), "Failed\nHISTORY:\n");
+
+        let e = track!(e);
+        assert_eq!(e.history_len(), 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_tag_works() {
+        let e = track!(Failed.error());
+        let e = track!(e, tag = "retry");
+        let e = track!(e, "plain");
+        let e = track!(e, tag = "fallback", "using cache");
+        let e = track!(e, tag = "retry", "attempt={}", 2);
+
+        let history = e.history().unwrap();
+        let tags = history.events().iter().map(|l| l.tag()).collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            [None, Some("retry"), None, Some("fallback"), Some("retry")]
+        );
+
+        let retries = history.entries_with_tag("retry").collect::<Vec<_>>();
+        assert_eq!(retries.len(), 2);
+        assert_eq!(retries[0].message(), "");
+        assert_eq!(retries[1].message(), "attempt=2");
+        assert_eq!(history.entries_with_tag("fallback").count(), 1);
+        assert_eq!(history.entries_with_tag("validation").count(), 0);
+
+        let location = Location::new("foo", "src/foo.rs", 10, "bar").with_tag("retry");
+        assert_eq!(location.to_string(), "at src/foo.rs:10 #retry -- bar");
+
+        // Locations with different tags are not deduplicated
+        let mut history = History::new();
+        history.add_dedup(location.clone());
+        history.add_dedup(location.clone());
+        history.add_dedup(Location::new("foo", "src/foo.rs", 10, "bar"));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.events()[0].repeat_count(), 2);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialize_location_tag_works() {
+        extern crate serde_json;
+
+        let location = Location::new("foo", "src/foo.rs", 10, "bar").with_tag("retry");
+        let json = serde_json::to_value(&location).unwrap();
+        assert_eq!(json["tag"], "retry");
+        let d: Location = serde_json::from_value(json).unwrap();
+        assert_eq!(d.tag(), Some("retry"));
+        assert!(d.is_same_site(&location));
+
+        let location = Location::new("foo", "src/foo.rs", 10, "bar");
+        let json = serde_json::to_value(&location).unwrap();
+        assert!(json.get("tag").is_none());
+        let d: Location = serde_json::from_value(json).unwrap();
+        assert_eq!(d.tag(), None);
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn smallvec_history_works() {
+        let mut history = History::new();
+        for i in 0..3 {
+            history.add(i);
+        }
+        assert!(!history.events.spilled());
+        assert_eq!(history.events(), [0, 1, 2]);
+
+        for i in 3..10 {
+            history.add(i);
+        }
+        assert!(history.events.spilled());
+        assert_eq!(history.events(), (0..10).collect::<Vec<_>>().as_slice());
+        assert_eq!(history.first(), Some(&0));
+        assert_eq!(history.last(), Some(&9));
+
+        history.set_max_len(2);
+        assert_eq!(history.events(), [8, 9]);
+        assert_eq!(history.elided_count(), 8);
+
+        let mut e = Failed.error();
+        for _ in 0..3 {
+            e = track!(e);
+        }
+        assert!(!e.history().unwrap().events.spilled());
+        for _ in 0..10 {
+            e = track!(e);
+        }
+        assert!(e.history().unwrap().events.spilled());
+        assert_eq!(e.history().unwrap().len(), 13);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_first_last_works() {
+        let mut history = History::new();
+        assert_eq!(history.first(), None);
+        assert_eq!(history.last(), None);
+
+        history.add(0);
+        assert_eq!(history.first(), Some(&0));
+        assert_eq!(history.last(), Some(&0));
+
+        history.set_max_len(3);
+        for i in 1..5 {
+            history.add(i);
+        }
+        assert_eq!(history.first(), Some(&2));
+        assert_eq!(history.last(), Some(&4));
+
+        let e = track!(track!(Failed.error(), "origin"), "latest");
+        let history = e.history().unwrap();
+        assert_eq!(history.first().map(|l| l.message()), Some("origin"));
+        assert_eq!(history.last().map(|l| l.message()), Some("latest"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_events_rev_works() {
+        let mut history = History::new();
+        for i in 0..5 {
+            history.add(i);
+        }
+        history.set_max_len(4);
+
+        let events = history.events_rev().cloned().collect::<Vec<_>>();
+        assert_eq!(events, [4, 3, 2, 1]);
+        assert!(events.iter().eq(history.events().iter().rev()));
+
+        let mut e = Failed.error();
+        {
+            let history = e.history_mut().unwrap();
+            history.set_max_len(2);
+            for i in 0..3 {
+                history.add(Location::new("foo", "src/foo.rs", 10 * i, ""));
+            }
+        }
+        assert_eq!(
+            strip_thread(&e.display_reversed().to_string()),
+            "Failed\nHISTORY:\n  [0] at src/foo.rs:20\n  [1] at src/foo.rs:10\n  ... 1 earlier entries elided\n"
+        );
         assert_eq!(
-            format!("\n{}", error).replace('\\', "/"),
+            strip_thread(&e.to_string()),
+            "Failed\nHISTORY:\n  ... 1 earlier entries elided\n  [1] at src/foo.rs:10\n  [2] at src/foo.rs:20\n"
+        );
+    }
+
+    #[test]
+    fn history_join_works() {
+        let mut a = History::new();
+        a.add(0);
+        a.add(1);
+        let mut b = History::new();
+        b.add(2);
+        b.add(3);
+
+        let mut c = History::new();
+        c.extend_from(&a);
+        c.extend_from(&b);
+        assert_eq!(c.events(), [0, 1, 2, 3]);
+
+        let history = a.join(b);
+        assert_eq!(history.events(), [0, 1, 2, 3]);
+
+        let mut a = History::new();
+        a.set_max_len(3);
+        a.add(0);
+        a.add(1);
+        let history = a.join(c);
+        assert_eq!(history.events(), [1, 2, 3]);
+        assert_eq!(history.elided_count(), 3);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn multiline_message_works() {
+        let mut e = Failed.error();
+        {
+            let history = e.history_mut().unwrap();
+            history.add(Location::new(
+                "foo",
+                "src/foo.rs",
+                10,
+                "expected: 1\nactual: 2",
+            ));
+            for _ in 0..9 {
+                history.add(Location::new("foo", "src/foo.rs", 20, ""));
+            }
+            history.add(Location::new("foo", "src/foo.rs", 30, "a\nb\n"));
+        }
+        let expected = r#"
+  [0] at src/foo.rs:10 -- expected: 1
+      actual: 2"#;
+        assert!(strip_thread(&format!("\n{}", e)).contains(expected));
+        assert!(strip_thread(&format!("\n{}", e.history().unwrap())).contains(expected));
+        assert!(strip_thread(&e.to_string()).ends_with(
             r#"
-Failed (cause; NotFound)
-HISTORY:
-  [0] at src/lib.rs:354
-  [1] at src/lib.rs:359
-  [2] at src/lib.rs:363
+  [10] at src/foo.rs:30 -- a
+       b
+
 "#
+        ));
+
+        let options = DisplayOptions::new()
+            .with_indent("\t")
+            .with_numbering(false);
+        assert!(strip_thread(&e.display_with(options).to_string())
+            .contains("\tat src/foo.rs:10 -- expected: 1\n\tactual: 2"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn custom_event_works() {
+        #[derive(Debug)]
+        struct Event {
+            line: u32,
+            seq: usize,
+        }
+        impl From<Location> for Event {
+            fn from(f: Location) -> Self {
+                Event {
+                    line: f.line(),
+                    seq: 0,
+                }
+            }
+        }
+
+        #[derive(Default)]
+        struct Object {
+            history: History<Event>,
+        }
+        impl Trackable for Object {
+            type Event = Event;
+
+            fn track<F>(&mut self, f: F)
+            where
+                F: FnOnce() -> Self::Event,
+            {
+                let mut event = f();
+                event.seq = self.history.events().len();
+                self.history.add(event);
+            }
+            fn history(&self) -> Option<&History<Self::Event>> {
+                Some(&self.history)
+            }
+            fn history_mut(&mut self) -> Option<&mut History<Self::Event>> {
+                Some(&mut self.history)
+            }
+        }
+
+        let line = line!() + 1;
+        let o = track!(Object::default());
+        let o = track!(o, "hello");
+
+        let events = o.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].line, line);
+        assert_eq!(events[0].seq, 0);
+        assert_eq!(events[1].line, line + 1);
+        assert_eq!(events[1].seq, 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn history_max_len_works() {
+        let mut e = Failed.error();
+        e.history_mut().unwrap().set_max_len(10);
+        for _ in 0..1000 {
+            e = track!(e);
+        }
+
+        let history = e.history().unwrap();
+        assert_eq!(history.events().len(), 10);
+        assert_eq!(history.elided_count(), 990);
+
+        let s = e.to_string();
+        let lines = s.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[1], "HISTORY:");
+        assert_eq!(lines[2], "  ... 990 earlier entries elided");
+        for (i, line) in lines[3..].iter().enumerate() {
+            assert!(line.starts_with(&format!("  [{}] at src/lib.rs:", 990 + i)));
+        }
+    }
+
+    #[cfg(all(feature = "serialize", feature = "time"))]
+    #[test]
+    fn serialize_timestamp_works() {
+        extern crate serde_json;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let e = track!(track!(Failed.error()));
+        let json = serde_json::to_value(&e).unwrap();
+        let history = json["history"].as_array().unwrap();
+        for (entry, location) in history.iter().zip(e.history().unwrap().events()) {
+            let timestamp = entry["timestamp"].as_str().unwrap();
+            assert!(timestamp.ends_with('Z'));
+            let d: Location = serde_json::from_value(entry.clone()).unwrap();
+            assert_eq!(d.timestamp(), location.timestamp());
+        }
+
+        let cases = [
+            (UNIX_EPOCH, "1970-01-01T00:00:00Z"),
+            (
+                UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                "2023-11-14T22:13:20Z",
+            ),
+            (
+                UNIX_EPOCH + Duration::from_millis(1_700_000_000_500),
+                "2023-11-14T22:13:20.5Z",
+            ),
+            (
+                UNIX_EPOCH + Duration::from_secs(951_782_400),
+                "2000-02-29T00:00:00Z",
+            ),
+            (
+                UNIX_EPOCH - Duration::from_millis(1500),
+                "1969-12-31T23:59:58.5Z",
+            ),
+        ];
+        for &(t, s) in &cases {
+            let mut location = Location::new("", "a.rs", 1, "");
+            location.timestamp = Some(t);
+            let json = serde_json::to_value(&location).unwrap();
+            assert_eq!(json["timestamp"], s);
+            let d: Location = serde_json::from_value(json).unwrap();
+            assert_eq!(d.timestamp(), Some(t));
+        }
+
+        let parse = |timestamp: &str| {
+            let json = format!(r#"{{"file":"a.rs","line":1,"timestamp":{}}}"#, timestamp);
+            serde_json::from_str::<Location>(&json).map(|l| l.timestamp())
+        };
+        let t = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(parse(r#""2023-11-15T07:13:20+09:00""#).ok(), Some(Some(t)));
+        assert_eq!(parse(r#""2023-11-14t22:13:20.000z""#).ok(), Some(Some(t)));
+        assert_eq!(
+            parse(r#"{"secs_since_epoch":1700000000,"nanos_since_epoch":0}"#).ok(),
+            Some(Some(t))
+        );
+        assert_eq!(parse("null").ok(), Some(None));
+        assert!(parse(r#""2023-11-14T22:13:20""#).is_err());
+        assert!(parse(r#""2023-13-14T22:13:20Z""#).is_err());
+        assert!(parse(r#""2023-11-14T22:13:20.Z""#).is_err());
+        assert!(parse(r#""2023-11-14T22:13:20+0900""#).is_err());
+        assert!(parse("\"2023-11-14T22:13:0\u{e9}000Z\"").is_err());
+        assert!(parse("\"2023-11-14T22:13:20.5\u{e9}\"").is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_timestamp_works() {
+        let e = track!(Failed.error());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let e = track!(e);
+
+        let events = e.history().unwrap().events();
+        let t0 = events[0].timestamp().unwrap();
+        let t1 = events[1].timestamp().unwrap();
+        assert!(t0 <= t1);
+
+        let s = format!("{}", e);
+        let lines = s.lines().collect::<Vec<_>>();
+        assert!(!lines[2].contains("(+"));
+        assert!(lines[3].contains("(+"));
+        assert!(lines[3].ends_with("ms)"));
+    }
+
+    #[cfg(feature = "seq")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_seq_works() {
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel();
+        let workers = (0..2)
+            .map(|_| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    let mut e = Failed.error();
+                    for _ in 0..100 {
+                        e = track!(e);
+                    }
+                    tx.send(e).unwrap();
+                })
+            })
+            .collect::<Vec<_>>();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let mut seqs = Vec::new();
+        for e in rx.try_iter() {
+            let events = e.history().unwrap().events();
+            assert_eq!(events.len(), 100);
+            assert!(events.windows(2).all(|w| w[0].seq() < w[1].seq()));
+            seqs.extend(events.iter().map(|l| l.seq()));
+        }
+        assert_eq!(seqs.len(), 200);
+        seqs.sort_unstable();
+        seqs.dedup();
+        assert_eq!(seqs.len(), 200);
+
+        let location = Location::new("foo", "src/foo.rs", 10, "bar");
+        assert_eq!(location.to_string(), "at src/foo.rs:10 -- bar");
+        assert_eq!(
+            format!("{:#}", location),
+            format!("at src/foo.rs:10 (foo) {{seq: {}}} -- bar", location.seq())
         );
     }
+
+    #[cfg(feature = "tracing")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_span_works() {
+        extern crate tracing_subscriber;
+        use tracing::instrument;
+
+        #[instrument]
+        fn handle_request(id: u32) -> Failure {
+            track!(Failure::from(Failed.error()), "id={}", id)
+        }
+
+        let subscriber = tracing_subscriber::registry();
+        let e = tracing::subscriber::with_default(subscriber, || handle_request(3));
+        let e = track!(e);
+
+        let events = e.history().unwrap().events();
+        assert_eq!(events[0].span_name(), Some("handle_request"));
+        assert_eq!(events[0].span_target(), Some(module_path!()));
+        assert_eq!(events[1].span_name(), None);
+
+        let s = e.to_string();
+        let lines = s.lines().collect::<Vec<_>>();
+        assert!(lines[2].ends_with(" {span: handle_request} -- id=3"));
+        assert!(!lines[3].contains("{span:"));
+    }
+
+    #[cfg(feature = "thread")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn location_thread_works() {
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("worker-3".to_owned())
+            .spawn(move || {
+                let e = track!(Failed.error());
+                tx.send(track!(e)).unwrap();
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        let e = track!(rx.recv().unwrap());
+
+        let events = e.history().unwrap().events();
+        assert_eq!(events[0].thread_name(), Some("worker-3"));
+        assert_eq!(events[1].thread_name(), Some("worker-3"));
+        let current = std::thread::current();
+        assert_eq!(events[2].thread_name(), current.name());
+
+        let s = format!("{}", e);
+        let lines = s.lines().collect::<Vec<_>>();
+        assert!(lines[2].ends_with(" {thread: worker-3}"));
+        assert!(!lines[3].contains("{thread:"));
+        let annotation = format!(" {{thread: {}}}", current.name().unwrap());
+        assert!(lines[4].ends_with(&annotation));
+    }
 }
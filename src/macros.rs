@@ -3,6 +3,20 @@
 /// `$target` must be evaluated to a value which implements [Trackable](trait.Trackable.html) trait.
 ///
 /// If `$target.in_tracking()` is `false`, it will simply return the value of `$target` untouched.
+/// The same applies to every target if the `disable_tracking` feature is enabled.
+///
+/// A message can be given as the second argument. It is either
+///
+/// - an expression of a type that implements `Into<Cow<'static, str>>`
+///   (e.g., a string literal or an owned `String`, which is stored without copying), or
+/// - format arguments (e.g., `track!(e, "id={}", id)`), which are formatted into an owned `String`.
+///
+/// Borrowed strings that are not `'static` need to be converted to `String` (or passed as format arguments)
+/// because a [`Location`](struct.Location.html) owns its message.
+///
+/// The message can also be given as a closure without arguments (e.g., `track!(r, || format!(...))`).
+/// The closure is called only if the location is actually tracked,
+/// so nothing is formatted for an `Ok` result or a `None` option.
 ///
 /// # Examples
 ///
@@ -11,6 +25,7 @@
 /// # extern crate trackable;
 /// #
 /// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
 /// use trackable::error::{Failed, ErrorKindExt};
 ///
 /// // Makes a `TrackableError` value
@@ -26,41 +41,169 @@
 /// let e = Some(e);
 /// let e = track!(e, "Hello {}", "World!");
 ///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
 /// assert_eq!(format!("\n{}", e.unwrap().err().unwrap()).replace('\\', "/"), r#"
 /// Failed (cause; something wrong)
 /// HISTORY:
-///   [0] at src/macros.rs:10
-///   [1] at src/macros.rs:15 -- message="This is a note about this location"
-///   [2] at src/macros.rs:19 -- Hello World!
+///   [0] at src/macros.rs:10:9
+///   [1] at src/macros.rs:15:9 -- message="This is a note about this location"
+///   [2] at src/macros.rs:19:9 -- Hello World!
+/// "#);
+/// # }
+/// ```
+///
+/// The closure form makes it explicit that an expensive message costs nothing on the success path:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt, TrackableError};
+///
+/// fn describe(n: usize) -> String {
+///     (0..n).map(|i| i.to_string()).collect::<Vec<_>>().join(",")
+/// }
+///
+/// let r: Result<(), TrackableError<Failed>> = Ok(());
+/// assert!(track!(r, || describe(1000)).is_ok());
+///
+/// let r: Result<(), TrackableError<Failed>> = Err(Failed.error());
+/// let e = track!(r, || describe(3)).err().unwrap();
+/// # if cfg!(feature = "disable_tracking") { return; }
+/// assert_eq!(e.history().unwrap().events()[0].message(), "0,1,2");
+/// # }
+/// ```
+///
+/// The name of the enclosing function can be recorded by the `fn = $name` form
+/// (see [`Location::function`](struct.Location.html#method.function)).
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// fn handle_request() -> trackable::error::TrackableError<Failed> {
+///     track!(Failed.error(), fn = "handle_request")
+/// }
+///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// assert_eq!(format!("\n{}", handle_request()).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/macros.rs:9:5 [handle_request]
 /// "#);
 /// # }
 /// ```
+///
+/// A tag that categorizes the location can be given by the `tag = $tag` form,
+/// optionally followed by a message
+/// (see [`Location::tag`](struct.Location.html#method.tag)).
+/// The tag is shown as `#$tag` in the `Display` form.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let e = track!(Failed.error(), tag = "retry");
+/// let e = track!(e, tag = "fallback", "attempt={}", 2);
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/macros.rs:8:9 #retry
+///   [1] at src/macros.rs:9:9 #fallback -- attempt=2
+/// "#);
+/// # }
+/// ```
+///
+/// The result of this macro must be used (the `unused_must_use` lint warns otherwise),
+/// because a tracked value that is dropped immediately is usually a mistake.
+///
+/// ```compile_fail
+/// #![deny(unused_must_use)]
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// # fn main() {
+/// let e = Failed.error();
+/// track!(e); // error: unused return value
+/// # }
+/// ```
 #[macro_export]
 macro_rules! track {
     ($target:expr) => {
         {
-            use $crate::Trackable;
             let mut target = $target;
-            target.track(|| {
-                let location = $crate::Location::new(
-                    module_path!(), file!(), line!(), String::new());
-                From::from(location)
-            });
-            target
+            if $crate::TRACKING_ENABLED {
+                $crate::Trackable::track(&mut target, || {
+                    let location = $crate::Location::new(
+                        module_path!(), file!(), line!(), "").with_column(column!());
+                    From::from(location)
+                });
+            }
+            $crate::__must_use(target)
+        }
+    };
+    ($target:expr, fn = $function:expr) => {
+        {
+            let mut target = $target;
+            if $crate::TRACKING_ENABLED {
+                $crate::Trackable::track(&mut target, || {
+                    let location = $crate::Location::new(
+                        module_path!(), file!(), line!(), "").with_column(column!()).with_function($function);
+                    From::from(location)
+                });
+            }
+            $crate::__must_use(target)
+        }
+    };
+    ($target:expr, tag = $tag:expr) => {
+        $crate::track!($target, tag = $tag, "")
+    };
+    ($target:expr, tag = $tag:expr, $message:expr) => {
+        {
+            let mut target = $target;
+            if $crate::TRACKING_ENABLED {
+                $crate::Trackable::track(&mut target, || {
+                    let location = $crate::Location::new(module_path!(), file!(), line!(), $message)
+                        .with_column(column!())
+                        .with_tag($tag);
+                    From::from(location)
+                });
+            }
+            $crate::__must_use(target)
         }
     };
+    ($target:expr, tag = $tag:expr, $($format_arg:tt)+) => {
+        $crate::track!($target, tag = $tag, $crate::format!($($format_arg)+))
+    };
     ($target:expr; $($value:expr),+) => {
         $crate::track!($target, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
     };
+    ($target:expr, || $message:expr) => {
+        $crate::track!($target, $message)
+    };
     ($target:expr, $message:expr) => {
         {
-            use $crate::Trackable;
             let mut target = $target;
-            target.track(|| {
-                let location = $crate::Location::new(module_path!(), file!(), line!(), $message);
-                From::from(location)
-            });
-            target
+            if $crate::TRACKING_ENABLED {
+                $crate::Trackable::track(&mut target, || {
+                    let location = $crate::Location::new(module_path!(), file!(), line!(), $message)
+                        .with_column(column!());
+                    From::from(location)
+                });
+            }
+            $crate::__must_use(target)
         }
     };
     ($target:expr, $message:expr; $($value:expr),+) => {
@@ -68,7 +211,399 @@ macro_rules! track {
     };
     ($target:expr, $($format_arg:tt)+) => {
         {
-            $crate::track!($target, format!($($format_arg)+))
+            $crate::track!($target, $crate::format!($($format_arg)+))
+        }
+    };
+}
+
+/// Same as [`track!`](macro.track.html) but also emits a log record about the tracked location.
+///
+/// This macro is available only if the `log` feature is enabled.
+///
+/// `$target` must be a `TrackableError` (or a newtype of it) so that the level of the record
+/// can be derived from its [severity](error/trait.ErrorKind.html#method.severity).
+/// The target of the record is the module path where the macro is called.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// // Emits a record like "at src/foo.rs:10:9 -- connecting: Failed (cause; refused)"
+/// // at the `Error` level.
+/// let e = track_log!(Failed.cause("refused"), "connecting");
+/// # }
+/// ```
+#[cfg(feature = "log")]
+#[macro_export]
+macro_rules! track_log {
+    ($target:expr) => {
+        $crate::track_log!($target, "")
+    };
+    ($target:expr; $($value:expr),+) => {
+        $crate::track_log!($target, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+    };
+    ($target:expr, $message:expr) => {
+        {
+            let target = $crate::track!($target, $message);
+            $crate::error::log_tracked(module_path!(), target.severity(), &target);
+            target
+        }
+    };
+    ($target:expr, $message:expr; $($value:expr),+) => {
+        $crate::track_log!($target, concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    };
+    ($target:expr, $($format_arg:tt)+) => {
+        {
+            $crate::track_log!($target, $crate::format!($($format_arg)+))
+        }
+    };
+}
+
+/// Same as [`track!`](macro.track.html) but deduplicates consecutive identical locations.
+///
+/// If the location (and the message) is the same as the last one in the history of `$target`,
+/// the repeat count of the last one is incremented instead of adding a new entry
+/// (see [`History::add_dedup`](struct.History.html#method.add_dedup)).
+/// This is useful to avoid growing the history in retry loops.
+///
+/// `$target` must be a value that implements `Trackable<Event = Location>`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let mut e = Failed.error();
+/// for _ in 0..17 {
+///     e = track_dedup!(e);
+/// }
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/macros.rs:10:9 (x17)
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_dedup {
+    ($target:expr) => {
+        $crate::track_dedup!($target, "")
+    };
+    ($target:expr; $($value:expr),+) => {
+        $crate::track_dedup!($target, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+    };
+    ($target:expr, $message:expr) => {
+        {
+            let mut target = $target;
+            if $crate::TRACKING_ENABLED {
+                if let Some(history) = $crate::Trackable::history_mut(&mut target) {
+                    let location = $crate::Location::new(module_path!(), file!(), line!(), $message)
+                        .with_column(column!());
+                    history.add_dedup(location);
+                }
+            }
+            $crate::__must_use(target)
+        }
+    };
+    ($target:expr, $message:expr; $($value:expr),+) => {
+        $crate::track_dedup!($target, concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    };
+    ($target:expr, $($format_arg:tt)+) => {
+        {
+            $crate::track_dedup!($target, $crate::format!($($format_arg)+))
+        }
+    };
+}
+
+/// Same as [`track!`](macro.track.html) but only tracks the location if `debug_assertions` is enabled.
+///
+/// If `debug_assertions` is disabled (e.g., in release builds),
+/// it simply returns the value of `$target` untouched
+/// (i.e., no `Location` is created and the history is not changed).
+/// Note that `$target` is always evaluated.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let e = track_debug!(Failed.error(), "hot path");
+/// let history_len = e.history().unwrap().events().len();
+/// # if cfg!(feature = "disable_tracking") { return; }
+/// if cfg!(debug_assertions) {
+///     assert_eq!(history_len, 1);
+/// } else {
+///     assert_eq!(history_len, 0);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_debug {
+    ($target:expr) => {
+        {
+            let target = $target;
+            if cfg!(debug_assertions) {
+                $crate::track!(target)
+            } else {
+                target
+            }
+        }
+    };
+    ($target:expr; $($arg:tt)*) => {
+        {
+            let target = $target;
+            if cfg!(debug_assertions) {
+                $crate::track!(target; $($arg)*)
+            } else {
+                target
+            }
+        }
+    };
+    ($target:expr, $($arg:tt)*) => {
+        {
+            let target = $target;
+            if cfg!(debug_assertions) {
+                $crate::track!(target, $($arg)*)
+            } else {
+                target
+            }
+        }
+    };
+}
+
+/// Makes a future that tracks the current location into the history of the output of `$future`.
+///
+/// This is intended to record the location of an `.await` point.
+/// Note that the resulting future needs to be awaited by the caller
+/// (e.g., `track_await!(fut).await?`), since this crate is written in the 2015 edition
+/// and its macros cannot expand to `.await`.
+///
+/// The output of `$future` must implement [Trackable](trait.Trackable.html)
+/// (e.g., `Result<T, E: Trackable>`, in which case only errors are tracked).
+/// The location is created when the future completes, and the message forms are the same as
+/// [`track!`](macro.track.html).
+///
+/// # Examples
+///
+/// ```edition2018
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// use std::future::Future;
+/// use std::pin::Pin;
+/// use std::sync::Arc;
+/// use std::task::{Context, Poll, Wake, Waker};
+/// use trackable::error::{Failed, Failure};
+///
+/// async fn connect() -> Result<(), Failure> {
+///     Err(Failed.into())
+/// }
+///
+/// async fn run() -> Result<(), Failure> {
+///     track_await!(connect(), "connecting").await?;
+///     Ok(())
+/// }
+///
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// # struct NoopWaker;
+/// # impl Wake for NoopWaker {
+/// #     fn wake(self: Arc<Self>) {}
+/// # }
+/// # let waker = Waker::from(Arc::new(NoopWaker));
+/// # let mut cx = Context::from_waker(&waker);
+/// // Polls the future with a minimal executor
+/// let mut future = Box::pin(run());
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// let Poll::Ready(result) = future.as_mut().poll(&mut cx) else { panic!() };
+/// assert_eq!(format!("\n{}", result.unwrap_err()).replace('\\', "/"), r#"
+/// Failed
+/// HISTORY:
+///   [0] at src/macros.rs:15:5 -- connecting
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_await {
+    ($future:expr) => {
+        $crate::track_await!($future, "")
+    };
+    ($future:expr; $($value:expr),+) => {
+        $crate::track_await!($future, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+    };
+    ($future:expr, $message:expr) => {
+        $crate::TrackAwait::new($future, if $crate::TRACKING_ENABLED {
+            Some(|| {
+                $crate::Location::new(module_path!(), file!(), line!(), $message)
+                    .with_column(column!())
+            })
+        } else {
+            None
+        })
+    };
+    ($future:expr, $message:expr; $($value:expr),+) => {
+        $crate::track_await!($future, concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    };
+    ($future:expr, $($format_arg:tt)+) => {
+        $crate::track_await!($future, $crate::format!($($format_arg)+))
+    };
+}
+
+/// Evaluates `$block` as a scope labeled `$label`, and tracks the entry and the exit of the scope
+/// into the history of its result.
+///
+/// `$block` is evaluated in a closure, so `return` and `?` in it exit only the scope
+/// (i.e., they are also tracked), not the enclosing function,
+/// and the result is what the block returns or evaluates to.
+/// For the same reason, `.await` cannot be used in `$block` (even in an `async` function),
+/// and `break` and `continue` in it cannot refer to loops outside of it.
+/// The result must implement [Trackable](trait.Trackable.html)
+/// (e.g., `Result<T, E: Trackable>`, in which case only errors are tracked).
+///
+/// If the result is being tracked, two locations (both pointing to the macro invocation)
+/// are added to its history: `enter: $label` created when entering the scope,
+/// and `exit: $label` created when exiting it.
+/// The former is inserted at the head of the history and the latter is added to the tail,
+/// so that they enclose the entries added in the scope.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// fn parse(s: &str) -> Result<u32, Failure> {
+///     track_scope!("parse", {
+///         if s.is_empty() {
+///             return Err(track!(Failure::from(Failed.cause("empty"))));
+///         }
+///         let n = track!(s.parse::<u32>().map_err(Failure::from_error))?;
+///         Ok(n)
+///     })
+/// }
+///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// assert_eq!(parse("10").ok(), Some(10));
+/// assert_eq!(format!("\n{}", parse("").unwrap_err()).replace('\\', "/"), r#"
+/// Failed (cause; empty)
+/// HISTORY:
+///   [0] at src/macros.rs:9:5 -- enter: parse
+///   [1] at src/macros.rs:11:24
+///   [2] at src/macros.rs:9:5 -- exit: parse
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_scope {
+    ($label:expr, $block:block) => {{
+        let label = $label;
+        let enter = if $crate::TRACKING_ENABLED {
+            Some(
+                $crate::Location::new(
+                    module_path!(),
+                    file!(),
+                    line!(),
+                    $crate::format!("enter: {}", label),
+                )
+                .with_column(column!()),
+            )
+        } else {
+            None
+        };
+        #[allow(unused_mut, clippy::redundant_closure_call)]
+        let mut result = (|| $block)();
+        if let Some(enter) = enter {
+            if let Some(history) = $crate::Trackable::history_mut(&mut result) {
+                history.insert(0, From::from(enter));
+            }
+            $crate::Trackable::track(&mut result, || {
+                let location = $crate::Location::new(
+                    module_path!(),
+                    file!(),
+                    line!(),
+                    $crate::format!("exit: {}", label),
+                )
+                .with_column(column!());
+                From::from(location)
+            });
+        }
+        result
+    }};
+}
+
+/// Same as [`track!`](macro.track.html) but only tracks the location if `$condition` is `true`.
+///
+/// If `$condition` is `false`, it simply returns the value of `$target` untouched
+/// (i.e., no `Location` is created and the history is not changed).
+/// Note that both `$condition` and `$target` are always evaluated.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let verbose = false;
+/// let e = track_if!(verbose, Failed.error());
+/// # if cfg!(feature = "disable_tracking") { return; }
+/// let e = track_if!(!verbose, e, "always tracked if not verbose");
+/// assert_eq!(e.history().unwrap().len(), 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_if {
+    ($condition:expr, $target:expr) => {
+        {
+            let target = $target;
+            if $condition {
+                $crate::track!(target)
+            } else {
+                target
+            }
+        }
+    };
+    ($condition:expr, $target:expr; $($arg:tt)*) => {
+        {
+            let target = $target;
+            if $condition {
+                $crate::track!(target; $($arg)*)
+            } else {
+                target
+            }
+        }
+    };
+    ($condition:expr, $target:expr, $($arg:tt)*) => {
+        {
+            let target = $target;
+            if $condition {
+                $crate::track!(target, $($arg)*)
+            } else {
+                target
+            }
         }
     };
 }
@@ -82,16 +617,18 @@ macro_rules! track {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
 /// use std::sync::mpsc;
 /// use trackable::error::{Failed, ErrorKindExt};
 ///
 /// let rx = mpsc::channel::<()>().1;
 /// let result = track_any_err!(rx.recv(), "sender dropped");
 ///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
 /// assert_eq!(format!("\n{}", result.err().unwrap()).replace('\\', "/"), r#"
 /// Failed (cause; receiving on a closed channel)
 /// HISTORY:
-///   [0] at src/macros.rs:10 -- sender dropped
+///   [0] at src/macros.rs:10:14 -- sender dropped
 /// "#);
 /// # }
 /// ```
@@ -109,6 +646,34 @@ macro_rules! track_any_err {
 }
 
 /// The abbreviation of `$target.map_err(|e| track!(e, ..))`.
+///
+/// `$target` must be evaluated to a `Result` whose error type implements [Trackable](trait.Trackable.html) trait.
+///
+/// Unlike `track!`, the location is tracked only if `$target` is `Err`
+/// (an `Ok` value is returned untouched without creating any `Location`).
+/// The control flow of the caller is not changed.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// let result: Result<(), Failure> = Err(Failed.cause("something wrong").into());
+/// let result = track_err!(result, "hello");
+///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// assert_eq!(format!("\n{}", result.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; something wrong)
+/// HISTORY:
+///   [0] at src/macros.rs:9:14 -- hello
+/// "#);
+/// # }
+/// ```
 #[macro_export]
 macro_rules! track_err {
     ($target:expr) => {
@@ -134,6 +699,7 @@ macro_rules! track_err {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
 /// use trackable::error::{Failed, Failure};
 ///
 /// fn add_positive_f32(a: f32, b: f32) -> Result<f32, Failure> {
@@ -145,11 +711,12 @@ macro_rules! track_err {
 /// assert_eq!(r.ok(), Some(5.0));
 ///
 /// let r = add_positive_f32(1.0, -2.0); // Err
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
 /// assert!(r.is_err());
 /// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
 /// Failed (cause; assertion failed: `a > 0.0 && b > 0.0`; a=1.0, b=-2.0)
 /// HISTORY:
-///   [0] at src/macros.rs:9
+///   [0] at src/macros.rs:9:5
 /// "#);
 /// # }
 /// ```
@@ -179,10 +746,35 @@ macro_rules! track_assert {
     };
 }
 
-/// Error trackable variant of the standard `assert_ne!` macro.
+/// Error trackable variant of the standard `assert_eq!` macro.
 ///
 /// Conceptually, `track_assert_eq!(left, right, error_kind)` is equivalent to
 /// `track_assert!(left == right, error_kind)`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use trackable::error::{Failed, Failure};
+///
+/// fn check_len(v: &[u8], len: usize) -> Result<(), Failure> {
+///     track_assert_eq!(v.len(), len, Failed, "unexpected length");
+///     Ok(())
+/// }
+///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// assert!(check_len(&[1, 2], 2).is_ok());
+/// assert_eq!(format!("\n{}", check_len(&[1, 2], 3).err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `left == right`; assertion failed: `(left == right)` (left: `2`, right: `3`): unexpected length)
+/// HISTORY:
+///   [0] at src/macros.rs:9:5
+/// "#);
+/// # }
+/// ```
 #[macro_export]
 macro_rules! track_assert_eq {
     ($left:expr, $right:expr, $error_kind:expr) => {
@@ -269,6 +861,7 @@ macro_rules! track_assert_ne {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
 /// use trackable::error::{Failed, Failure};
 ///
 /// fn trackable_checked_sub(a: u32, b: u32) -> Result<u32, Failure> {
@@ -280,11 +873,12 @@ macro_rules! track_assert_ne {
 /// assert_eq!(r.ok(), Some(8));
 ///
 /// let r = trackable_checked_sub(2, 10); // Err
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
 /// assert!(r.is_err());
 /// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
 /// Failed (cause; assertion failed: `a.checked_sub(b).is_some()`)
 /// HISTORY:
-///   [0] at src/macros.rs:9
+///   [0] at src/macros.rs:9:13
 /// "#);
 /// # }
 /// ```
@@ -319,6 +913,74 @@ macro_rules! track_assert_some {
     };
 }
 
+/// Converts an `Option` value to a `Result` that has a tracked error of `$error_kind` in the `None` case.
+///
+/// If `$option` is evaluated to `Some(v)`, this macro will return `Ok(v)` without touching any histories.
+/// Otherwise it will return `Err(e)`, where `e` is a `TrackableError` of `$error_kind`
+/// that has the current location in its history.
+/// If a message is given, it is used as the cause of the error.
+///
+/// Unlike [`track_assert_some!`](macro.track_assert_some.html), this macro does not return
+/// from the enclosing function, so the result can be handled by `?` or other combinators.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// use std::collections::HashMap;
+/// use trackable::error::{Failed, Failure};
+///
+/// fn lookup(m: &HashMap<&str, u32>, key: &str) -> Result<u32, Failure> {
+///     let v = track_or!(m.get(key), Failed, "no such key: {:?}", key)?;
+///     Ok(*v)
+/// }
+///
+/// let mut m = HashMap::new();
+/// m.insert("foo", 1);
+/// assert_eq!(lookup(&m, "foo").ok(), Some(1));
+///
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
+/// let e = lookup(&m, "bar").err().unwrap();
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; no such key: "bar")
+/// HISTORY:
+///   [0] at src/macros.rs:10:13
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_or {
+    ($option:expr, $error_kind:expr) => {
+        match $option {
+            Some(v) => Ok(v),
+            None => Err($crate::track!($crate::error::TrackableError::from($error_kind))),
+        }
+    };
+    ($option:expr, $error_kind:expr; $($value:expr),+) => {
+        $crate::track_or!($option, $error_kind, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+    };
+    ($option:expr, $error_kind:expr, $message:expr) => {
+        match $option {
+            Some(v) => Ok(v),
+            None => {
+                let e = $crate::error::ErrorKindExt::cause($error_kind, $message);
+                Err($crate::track!(e))
+            }
+        }
+    };
+    ($option:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        $crate::track_or!($option, $error_kind,
+                          concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    };
+    ($option:expr, $error_kind:expr, $($format_arg:tt)+) => {
+        $crate::track_or!($option, $error_kind, $crate::format!($($format_arg)+))
+    };
+}
+
 /// Error trackable variant of the standard `panic!` macro.
 ///
 /// This returns an `TrackableError` object as the result value of the calling function,
@@ -351,22 +1013,24 @@ macro_rules! track_assert_some {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
 /// use trackable::error::{Failed, Failure};
 ///
 /// fn foo<F>(f: F) -> Result<(), Failure> where F: FnOnce() -> Result<(), Failure> { f() }
 ///
 /// let e = foo(|| track_panic!(Failed) ).err().unwrap();
+/// # if cfg!(any(feature = "disable_tracking", feature = "thread")) { return; }
 /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
 /// Failed
 /// HISTORY:
-///   [0] at src/macros.rs:10
+///   [0] at src/macros.rs:10:16
 /// "#);
 ///
 /// let e = foo(|| track_panic!(Failed, "something {}", "wrong") ).err().unwrap();
 /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
 /// Failed (cause; something wrong)
 /// HISTORY:
-///   [0] at src/macros.rs:17
+///   [0] at src/macros.rs:18:16
 /// "#);
 /// # }
 /// ```
@@ -394,7 +1058,7 @@ macro_rules! track_panic {
     };
     ($error_kind:expr, $($format_arg:tt)+) => {
         {
-            track_panic!($error_kind, format!($($format_arg)+))
+            track_panic!($error_kind, $crate::format!($($format_arg)+))
         }
     };
 }
@@ -437,6 +1101,83 @@ macro_rules! track_try_unwrap {
     };
 }
 
+/// Defines an enum of error kinds together with its `ErrorKind` implementation.
+///
+/// Each variant has a description string, which is returned by `ErrorKind::description`
+/// (and hence used by the `Display` form of errors).
+/// Optionally, a variant can also have a code (`; code = "..."`), which is returned by `ErrorKind::code`.
+///
+/// `Debug` is derived for the enum automatically, as it is required by `ErrorKind`.
+/// Only unit variants are supported.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use trackable::error::{ErrorKind as TrackableErrorKind, ErrorKindExt};
+///
+/// define_error_kind! {
+///     /// Possible error kinds.
+///     #[derive(Clone, Copy, PartialEq, Eq)]
+///     pub enum ErrorKind {
+///         /// Input is invalid.
+///         InvalidInput => "invalid input"; code = "E1001",
+///         NotFound => "resource not found"; code = "E1002",
+///         Other => "other error",
+///     }
+/// }
+///
+/// # fn main() {
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// assert_eq!(ErrorKind::InvalidInput.description(), "invalid input");
+/// assert_eq!(ErrorKind::NotFound.code(), Some("E1002"));
+/// assert_eq!(ErrorKind::Other.code(), None);
+///
+/// let e = ErrorKind::NotFound.cause("no such user");
+/// assert_eq!(e.to_string(), "resource not found [E1002] (cause; no such user)\nHISTORY:\n");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! define_error_kind {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => $description:expr $(; code = $code:expr)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant
+            ),*
+        }
+        impl $crate::error::ErrorKind for $name {
+            fn description(&self) -> &str {
+                match *self {
+                    $($name::$variant => $description),*
+                }
+            }
+            fn code(&self) -> Option<&str> {
+                match *self {
+                    $($name::$variant => $crate::define_error_kind!(@code $($code)?)),*
+                }
+            }
+        }
+    };
+    (@code) => {
+        None
+    };
+    (@code $code:expr) => {
+        Some($code)
+    };
+}
+
 /// Implements the typical traits for a newtype $error of `TrackableError<$kind>`.
 ///
 /// The automatically implemented traits are `Deref`, `From`, `Display`, `Error`,
@@ -445,6 +1186,14 @@ macro_rules! track_try_unwrap {
 /// This macro is useful to reduce the boilerplate code when
 /// you define a your own trackable error type.
 ///
+/// The implementations of `Deref`, `Display` and `Error` can be skipped
+/// by specifying them in the `skip = [..]` list (e.g., to hand-write a custom `Display`).
+/// Note that `Error` requires `Display`, so you need to implement it by yourself if you skip `Display`.
+///
+/// `Clone` can be implemented in addition by specifying it in the `derive = [..]` list
+/// (this requires `$kind: Clone`).
+/// It is not implemented by default, so that the existing `#[derive(Clone)]` on newtypes keeps working.
+///
 /// # Examples
 ///
 /// ```
@@ -465,6 +1214,21 @@ macro_rules! track_try_unwrap {
 /// pub struct Error(TrackableError<ErrorKind>);
 /// derive_traits_for_trackable_error_newtype!(Error, ErrorKind);
 ///
+/// // Defines another one which has a custom `Display`.
+/// #[derive(Debug, Clone)]
+/// pub struct CustomError(TrackableError<ErrorKind>);
+/// derive_traits_for_trackable_error_newtype!(CustomError, ErrorKind, skip = [Display]);
+/// impl std::fmt::Display for CustomError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "custom error: {:#}", self.0)
+///     }
+/// }
+///
+/// // Defines another one which is cloneable if `ErrorKind` is cloneable.
+/// #[derive(Debug)]
+/// pub struct CloneableError(TrackableError<ErrorKind>);
+/// derive_traits_for_trackable_error_newtype!(CloneableError, ErrorKind, derive = [Clone]);
+///
 /// # fn main() {}
 /// ```
 #[deprecated(
@@ -474,24 +1238,40 @@ macro_rules! track_try_unwrap {
 #[macro_export]
 macro_rules! derive_traits_for_trackable_error_newtype {
     ($error:ident, $kind:ty) => {
-        impl ::std::ops::Deref for $error {
-            type Target = $crate::error::TrackableError<$kind>;
+        derive_traits_for_trackable_error_newtype!($error, $kind, skip = []);
+    };
+    ($error:ident, $kind:ty, derive = [$($derive:ident),*]) => {
+        derive_traits_for_trackable_error_newtype!($error, $kind, skip = [], derive = [$($derive),*]);
+    };
+    ($error:ident, $kind:ty, skip = [$($skip:ident),*], derive = [$($derive:ident),*]) => {
+        derive_traits_for_trackable_error_newtype!($error, $kind, skip = [$($skip),*]);
+        $(derive_traits_for_trackable_error_newtype!(@derive $derive $error, $kind);)*
+    };
+    ($error:ident, $kind:ty, skip = [$($skip:ident),*]) => {
+        derive_traits_for_trackable_error_newtype!(@unless Deref [$($skip),*] {
+            impl ::std::ops::Deref for $error {
+                type Target = $crate::error::TrackableError<$kind>;
 
-            #[inline]
-            fn deref(&self) -> &Self::Target {
-                &self.0
+                #[inline]
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
             }
-        }
-        impl ::std::fmt::Display for $error {
-            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                self.0.fmt(f)
+        });
+        derive_traits_for_trackable_error_newtype!(@unless Display [$($skip),*] {
+            impl ::std::fmt::Display for $error {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    self.0.fmt(f)
+                }
             }
-        }
-        impl ::std::error::Error for $error {
-            fn source(&self) -> Option<&(::std::error::Error + 'static)> {
-                self.0.source()
+        });
+        derive_traits_for_trackable_error_newtype!(@unless Error [$($skip),*] {
+            impl ::std::error::Error for $error {
+                fn source(&self) -> Option<&(dyn (::std::error::Error) + 'static)> {
+                    self.0.source()
+                }
             }
-        }
+        });
         impl $crate::Trackable for $error {
             type Event = $crate::Location;
 
@@ -525,6 +1305,23 @@ macro_rules! derive_traits_for_trackable_error_newtype {
             }
         }
     };
+    (@derive Clone $error:ident, $kind:ty) => {
+        impl Clone for $error where $kind: Clone {
+            #[inline]
+            fn clone(&self) -> Self {
+                $error(self.0.clone())
+            }
+        }
+    };
+    (@unless Deref [Deref $(, $rest:ident)*] $body:tt) => {};
+    (@unless Display [Display $(, $rest:ident)*] $body:tt) => {};
+    (@unless Error [Error $(, $rest:ident)*] $body:tt) => {};
+    (@unless $name:ident [$head:ident $(, $rest:ident)*] $body:tt) => {
+        derive_traits_for_trackable_error_newtype!(@unless $name [$($rest),*] $body);
+    };
+    (@unless $name:ident [] { $($body:tt)* }) => {
+        $($body)*
+    };
 }
 
 #[doc(hidden)]
@@ -541,7 +1338,17 @@ macro_rules! trackable_prepare_values_fmt {
 
 #[cfg(test)]
 mod test {
+    #[cfg(not(feature = "disable_tracking"))]
+    use error::TrackableError;
     use error::{ErrorKindExt, Failed, Failure};
+    #[cfg(not(any(feature = "std", feature = "disable_tracking")))]
+    use std::string::{String, ToString};
+    #[cfg(not(any(feature = "std", feature = "disable_tracking")))]
+    use std::vec::Vec;
+    #[cfg(not(feature = "disable_tracking"))]
+    use test::strip_thread;
+    #[cfg(not(feature = "disable_tracking"))]
+    use Trackable;
 
     #[test]
     fn track_works() {
@@ -558,6 +1365,56 @@ mod test {
         assert!(foo(Ok(())).is_ok());
     }
 
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_owned_message_works() {
+        fn message_of(e: Failure) -> String {
+            let events = e.history().unwrap().events();
+            events[events.len() - 1].message().to_string()
+        }
+
+        let name = "world".to_string();
+        let message = format!("hello {}", name);
+        let e = track!(Failed.error(), message);
+        assert_eq!(message_of(e.into()), "hello world");
+
+        let e = track!(Failed.error(), format!("hello {}", name));
+        assert_eq!(message_of(e.into()), "hello world");
+
+        let e = track!(Failed.error(), name.as_str().to_string());
+        assert_eq!(message_of(e.into()), "world");
+
+        let e = {
+            let temporary = String::from("temporary");
+            track!(Failed.error(), "{}", &temporary[..4])
+        };
+        assert_eq!(message_of(e.into()), "temp");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_closure_message_works() {
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let message = || {
+            calls.set(calls.get() + 1);
+            format!("call#{}", calls.get())
+        };
+
+        let r: Result<(), Failure> = Ok(());
+        assert!(track!(r, || message()).is_ok());
+        let o: Option<Failure> = None;
+        assert!(track!(o, || message()).is_none());
+        assert_eq!(calls.get(), 0);
+
+        let r: Result<(), Failure> = Err(Failed.error().into());
+        let e = track!(r, || message()).err().unwrap();
+        assert_eq!(calls.get(), 1);
+        assert_eq!(e.history().unwrap().events()[0].message(), "call#1");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
     #[test]
     fn track_assert_works() {
         fn add_positive_f32(a: f32, b: f32) -> Result<f32, Failure> {
@@ -571,18 +1428,394 @@ mod test {
         let r = add_positive_f32(1.0, -2.0); // Err
         assert!(r.is_err());
         assert_eq!(
-            format!("\n{}", r.err().unwrap()).replace('\\', "/"),
+            strip_thread(&format!("\n{}", r.err().unwrap()).replace('\\', "/")),
             r#"
 Failed (cause; assertion failed: `a > 0.0 && b > 0.0`)
 HISTORY:
-  [0] at src/macros.rs:564
+  [0] at src/macros.rs:1421:13
 "#
         );
     }
 
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_assert_eq_and_ne_works() {
+        fn eq(a: u32, b: u32) -> Result<(), Failure> {
+            track_assert_eq!(a, b, Failed);
+            Ok(())
+        }
+        fn ne(a: u32, b: u32) -> Result<(), Failure> {
+            track_assert_ne!(a, b, Failed, "a={}", a);
+            Ok(())
+        }
+
+        assert!(eq(1, 1).is_ok());
+        let e = eq(1, 2).err().unwrap();
+        let cause = e.causes().next().unwrap().to_string();
+        assert!(cause.contains("(left: `1`, right: `2`)"));
+        assert_eq!(e.history().unwrap().events().len(), 1);
+
+        assert!(ne(1, 2).is_ok());
+        let e = ne(3, 3).err().unwrap();
+        let cause = e.causes().next().unwrap().to_string();
+        assert!(cause.contains("(left != right)"));
+        assert!(cause.ends_with("(left: `3`, right: `3`): a=3"));
+    }
+
+    #[cfg(feature = "log")]
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_log_works() {
+        use error::{ErrorKind, Severity};
+        use log::{Level, Log, Metadata, Record};
+        use std::sync::Mutex;
+
+        struct MockLogger(Mutex<Vec<(Level, String, String)>>);
+        impl Log for MockLogger {
+            fn enabled(&self, _metadata: &Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &Record) {
+                let entry = (
+                    record.level(),
+                    record.target().to_owned(),
+                    record.args().to_string(),
+                );
+                self.0.lock().unwrap().push(entry);
+            }
+            fn flush(&self) {}
+        }
+        static LOGGER: MockLogger = MockLogger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        #[derive(Debug)]
+        struct Timeout;
+        impl ErrorKind for Timeout {
+            fn severity(&self) -> Severity {
+                Severity::Warning
+            }
+        }
+
+        let e = track_log!(Failed.cause("foo"));
+        let e: Failure = track_log!(e, "hello {}", 1).into();
+        let e = track_log!(e; 2);
+        let _ = track_log!(Timeout.takes_over(e), "timeout");
+
+        let records = LOGGER.0.lock().unwrap();
+        assert_eq!(records.len(), 4);
+        assert!(records.iter().all(|r| r.1 == module_path!()));
+        assert_eq!(
+            records.iter().map(|r| r.0).collect::<Vec<_>>(),
+            [Level::Error, Level::Error, Level::Error, Level::Warn]
+        );
+        assert!(records[0].2.starts_with("at src/macros.rs:"));
+        assert!(records[0].2.ends_with(": Failed (cause; foo)"));
+        assert!(records[1].2.ends_with(" -- hello 1: Failed (cause; foo)"));
+        assert!(records[2].2.ends_with(" -- 2=2: Failed (cause; foo)"));
+        assert!(records[3]
+            .2
+            .ends_with(" -- timeout: Timeout (severity; Warning) (cause; foo)"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_assert_some_works() {
+        fn get(v: Option<u32>) -> Result<u32, Failure> {
+            let n = track_assert_some!(v, Failed);
+            Ok(n)
+        }
+        fn get_with_message(v: Option<u32>, key: &str) -> Result<u32, Failure> {
+            let n = track_assert_some!(v, Failed, "key={}", key);
+            Ok(n)
+        }
+
+        assert_eq!(get(Some(10)).ok(), Some(10));
+        assert_eq!(get_with_message(Some(20), "foo").ok(), Some(20));
+
+        let e = get(None).err().unwrap();
+        assert_eq!(e.history().unwrap().len(), 1);
+        assert!(e
+            .to_string()
+            .starts_with("Failed (cause; assertion failed: `v.is_some()`)\n"));
+
+        let e = get_with_message(None, "foo").err().unwrap();
+        assert_eq!(e.history().unwrap().len(), 1);
+        assert!(e
+            .to_string()
+            .starts_with("Failed (cause; assertion failed: `v.is_some()`; key=foo)\n"));
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    #[allow(deprecated)]
+    fn derive_traits_with_skip_works() {
+        use error::{ErrorKind, TrackableError};
+        use std::error::Error;
+        use std::fmt;
+
+        #[derive(Debug)]
+        struct MyErrorKind;
+        impl ErrorKind for MyErrorKind {}
+
+        #[derive(Debug)]
+        struct MyError(TrackableError<MyErrorKind>);
+        derive_traits_for_trackable_error_newtype!(MyError, MyErrorKind, skip = [Display]);
+        impl fmt::Display for MyError {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "custom: {:#}", self.0)
+            }
+        }
+
+        let e: MyError = MyErrorKind.cause("foo").into();
+        let e = track!(e);
+        assert_eq!(e.to_string(), "custom: MyErrorKind (cause; foo)");
+        assert_eq!(e.source().unwrap().to_string(), "foo");
+        assert_eq!(e.history().unwrap().len(), 1);
+        assert_eq!(e.kind().description(), "An error");
+
+        let e: MyError = MyErrorKind.into();
+        let e: TrackableError<MyErrorKind> = e.into();
+        assert!(e.source().is_none());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    #[allow(deprecated)]
+    fn derive_traits_with_clone_works() {
+        use error::{ErrorKind, TrackableError};
+
+        #[derive(Debug, Clone, PartialEq)]
+        struct MyErrorKind;
+        impl ErrorKind for MyErrorKind {}
+
+        #[derive(Debug)]
+        struct MyError(TrackableError<MyErrorKind>);
+        derive_traits_for_trackable_error_newtype!(MyError, MyErrorKind, derive = [Clone]);
+
+        #[derive(Debug)]
+        struct MyError2(TrackableError<MyErrorKind>);
+        derive_traits_for_trackable_error_newtype!(
+            MyError2,
+            MyErrorKind,
+            skip = [Deref],
+            derive = [Clone]
+        );
+
+        let e0: MyError = track!(MyErrorKind.cause("foo").into());
+        let e1 = track!(e0.clone(), "cloned");
+        let e0 = track!(e0, "original");
+        assert_eq!(e0.history().unwrap().len(), 2);
+        assert_eq!(e1.history().unwrap().len(), 2);
+        assert_eq!(e0.history().unwrap().events()[1].message(), "original");
+        assert_eq!(e1.history().unwrap().events()[1].message(), "cloned");
+        assert_eq!(e0.kind(), e1.kind());
+
+        let e2: MyError2 = MyErrorKind.into();
+        let e3 = track!(e2.clone());
+        assert_eq!(e2.history().unwrap().len(), 0);
+        assert_eq!(e3.history().unwrap().len(), 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_err_works() {
+        let mut calls = 0;
+        let ok: Result<usize, Failure> = Ok(10);
+        let ok = track_err!(ok, "count={}", {
+            calls += 1;
+            calls
+        });
+        assert_eq!(ok.ok(), Some(10));
+        assert_eq!(calls, 0);
+
+        let err: Result<usize, Failure> = Err(Failed.error().into());
+        let err = track_err!(err);
+        let e = err.err().unwrap();
+        assert_eq!(e.history().unwrap().events().len(), 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_debug_works() {
+        let e = track_debug!(Failed.error());
+        let e = track_debug!(e, "hello");
+        let e = track_debug!(e, "baz.qux={}", 0);
+        let e = track!(e);
+
+        let history_len = e.history().unwrap().events().len();
+        #[cfg(debug_assertions)]
+        assert_eq!(history_len, 4);
+        #[cfg(not(debug_assertions))]
+        assert_eq!(history_len, 1);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn define_error_kind_works() {
+        use error::{ErrorKind, TrackableError};
+
+        define_error_kind! {
+            #[derive(Clone, PartialEq, Eq)]
+            enum Kind {
+                Foo => "foo error"; code = "E001",
+                /// The bar error.
+                Bar => "bar error",
+                Baz => "baz error"; code = "E003"
+            }
+        }
+
+        assert_eq!(Kind::Foo.description(), "foo error");
+        assert_eq!(Kind::Bar.description(), "bar error");
+        assert_eq!(Kind::Baz.description(), "baz error");
+        assert_eq!(Kind::Foo.code(), Some("E001"));
+        assert_eq!(Kind::Bar.code(), None);
+        assert_eq!(Kind::Baz.code(), Some("E003"));
+        assert_eq!(format!("{:?}", Kind::Bar), "Bar");
+        assert_eq!(Kind::Foo.clone(), Kind::Foo);
+
+        let e: TrackableError<Kind> = Kind::Bar.into();
+        assert_eq!(e.to_string(), "bar error\nHISTORY:\n");
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_scope_works() {
+        fn div(a: u32, b: u32) -> Result<u32, Failure> {
+            track_scope!("div", {
+                if b == 0 {
+                    return Err(Failed.cause("zero division").into());
+                }
+                Ok(a / b)
+            })
+        }
+        fn compute(a: u32, b: u32) -> Result<u32, Failure> {
+            track_scope!(format!("compute({}, {})", a, b), {
+                let n = track!(div(a, b))?;
+                Ok(n + 1)
+            })
+        }
+
+        assert_eq!(compute(4, 2).ok(), Some(3));
+
+        let e = compute(4, 0).unwrap_err();
+        let events = e.history().unwrap().events();
+        let messages = events.iter().map(|l| l.message()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "enter: compute(4, 0)",
+                "enter: div",
+                "exit: div",
+                "",
+                "exit: compute(4, 0)"
+            ]
+        );
+        assert_eq!(events[0].line(), events[4].line());
+        assert_eq!(events[1].line(), events[2].line());
+        assert_eq!(events[0].file(), file!());
+        assert_ne!(events[0].line(), events[1].line());
+
+        // Untracked results are returned untouched
+        let r: Option<Failure> = track_scope!("none", { None });
+        assert!(r.is_none());
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_scope_return_works() {
+        fn run(fail: bool, steps: &mut Vec<&'static str>) -> Result<u32, Failure> {
+            let result = track_scope!("scope", {
+                if fail {
+                    return Err(Failed.error().into());
+                }
+                steps.push("scope");
+                Ok(1)
+            });
+            // `return` in the block exits the scope, but not this function
+            steps.push("after");
+            result.map(|n| n + 1)
+        }
+
+        let mut steps = Vec::new();
+        assert_eq!(run(false, &mut steps).ok(), Some(2));
+        assert_eq!(steps, ["scope", "after"]);
+
+        let mut steps = Vec::new();
+        let e = run(true, &mut steps).unwrap_err();
+        assert_eq!(steps, ["after"]);
+        let messages = e
+            .history()
+            .unwrap()
+            .events()
+            .iter()
+            .map(|l| l.message())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["enter: scope", "exit: scope"]);
+    }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_if_works() {
+        let e = track_if!(false, Failed.error());
+        let e = track_if!(1 > 2, e, "hello");
+        let e = track_if!(false, e, "baz.qux={}", 0);
+        let e = track_if!(false, e; 1);
+        assert_eq!(e.history().unwrap().len(), 0);
+
+        let e = track_if!(true, e);
+        assert_eq!(e.history().unwrap().len(), 1);
+        let e = track_if!(2 > 1, e, "hello");
+        assert_eq!(e.history().unwrap().len(), 2);
+        let e = track_if!(true, e, "baz.qux={}", 0);
+        assert_eq!(e.history().unwrap().len(), 3);
+        let x = 1;
+        let e = track_if!(true, e; x);
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[1].message(), "hello");
+        assert_eq!(events[2].message(), "baz.qux=0");
+        assert_eq!(events[3].message(), "x=1");
+
+        let r: Result<(), Failure> = Ok(());
+        assert!(track_if!(true, r).is_ok());
+    }
+
     #[test]
     #[should_panic]
     fn track_try_unwrap_works() {
         track_try_unwrap!(Err(Failed.error()));
     }
+
+    #[cfg(not(feature = "disable_tracking"))]
+    #[test]
+    fn track_or_works() {
+        let r: Result<u32, TrackableError<Failed>> = track_or!(Some(1), Failed);
+        assert_eq!(r.ok(), Some(1));
+
+        let r: Result<u32, TrackableError<Failed>> = track_or!(None, Failed);
+        let e = r.err().unwrap();
+        assert_eq!(e.causes().count(), 0);
+        assert_eq!(e.history().unwrap().events().len(), 1);
+
+        let x = 10;
+        let r: Result<u32, TrackableError<Failed>> = track_or!(Some(2), Failed, "x={}", x);
+        assert_eq!(r.ok(), Some(2));
+
+        let r: Result<u32, TrackableError<Failed>> = track_or!(None, Failed, "x={}", x);
+        let e = r.err().unwrap();
+        assert_eq!(e.causes().next().unwrap().to_string(), "x=10");
+        assert_eq!(e.history().unwrap().events().len(), 1);
+
+        let r: Result<u32, TrackableError<Failed>> = track_or!(None, Failed, "oops"; x);
+        let e = r.err().unwrap();
+        assert_eq!(e.causes().next().unwrap().to_string(), "oops; x=10");
+
+        fn f(v: Option<u32>) -> Result<u32, Failure> {
+            let v = track_or!(v, Failed)?;
+            Ok(v + 1)
+        }
+        assert_eq!(f(Some(1)).ok(), Some(2));
+        assert!(f(None).is_err());
+    }
 }
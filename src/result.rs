@@ -1,6 +1,15 @@
 //! Trackable [`Result`] types for main and test functions.
 //!
 //! [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::process::{ExitCode, Termination};
+
+#[cfg(feature = "std")]
+use error::Failure;
 use error::TopLevelError;
 
 /// A variant of [`Result`] for top-level functions that return a trackable error on failure.
@@ -17,3 +26,78 @@ pub type MainResult = TopLevelResult;
 ///
 /// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
 pub type TestResult = TopLevelResult;
+
+/// A wrapper of the result of a main function that implements [`Termination`].
+///
+/// If the result is an error, the `Display` form of it (including the tracking history)
+/// is printed to the standard error and the process exits with a non-zero code.
+///
+/// This is available only if the `std` feature is enabled.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use trackable::error::{Failed, Failure};
+/// use trackable::result::Report;
+///
+/// fn run() -> Result<(), Failure> {
+///     track_panic!(Failed, "something wrong");
+/// }
+///
+/// fn main() -> Report {
+///     // Prints "Error: Failed (cause; something wrong)\nHISTORY:\n  [0] at ..." to stderr
+///     Report::from(run())
+/// }
+/// ```
+///
+/// [`Termination`]: https://doc.rust-lang.org/std/process/trait.Termination.html
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Report<E = Failure>(pub Result<(), E>);
+#[cfg(feature = "std")]
+impl<E: fmt::Display> Report<E> {
+    fn report_to<W: Write>(self, mut writer: W) -> ExitCode {
+        match self.0 {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                let _ = write!(writer, "Error: {}", e);
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl<E> From<Result<(), E>> for Report<E> {
+    fn from(f: Result<(), E>) -> Self {
+        Report(f)
+    }
+}
+#[cfg(feature = "std")]
+impl<E: fmt::Display> Termination for Report<E> {
+    fn report(self) -> ExitCode {
+        self.report_to(io::stderr().lock())
+    }
+}
+
+#[cfg(all(test, feature = "std", not(feature = "disable_tracking")))]
+mod test {
+    use super::*;
+    use error::{ErrorKindExt, Failed};
+
+    #[test]
+    fn report_works() {
+        let mut buf = Vec::new();
+        let code = Report::<Failure>(Ok(())).report_to(&mut buf);
+        assert_eq!(code, ExitCode::SUCCESS);
+        assert!(buf.is_empty());
+
+        let e: Failure = track!(Failed.cause("something wrong")).into();
+        let code = Report::from(Err(e)).report_to(&mut buf);
+        assert_eq!(code, ExitCode::FAILURE);
+
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.starts_with("Error: Failed (cause; something wrong)\nHISTORY:\n  [0] at "));
+    }
+}
@@ -0,0 +1,62 @@
+//! Checks the allocations made by errors.
+//!
+//! This is a separate test binary because it replaces the global allocator.
+#![cfg(not(feature = "backtrace"))]
+#[macro_use]
+extern crate trackable;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use trackable::error::{ErrorKindExt, Failed};
+use trackable::Trackable;
+
+thread_local! {
+    static COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+struct CountingAlloc;
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        COUNT.with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Returns the number of the allocations made by the current thread.
+fn count() -> usize {
+    COUNT.with(|c| c.get())
+}
+
+#[test]
+fn error_without_cause_does_not_allocate() {
+    let before = count();
+    let e = Failed.error();
+    let after = count();
+    assert_eq!(before, after);
+    assert!(e.history().unwrap().is_empty());
+
+    let e = track!(e);
+    if cfg!(feature = "disable_tracking") {
+        // Nothing is tracked
+        assert_eq!(count(), after);
+        assert!(e.history().unwrap().is_empty());
+        return;
+    }
+    assert_eq!(e.history().unwrap().len(), 1);
+    if cfg!(feature = "thread") {
+        // The location holds the name of the thread
+        return;
+    }
+    if cfg!(feature = "smallvec") {
+        // The location is stored inline
+        assert_eq!(count(), after);
+    } else {
+        assert!(count() > after);
+    }
+}
@@ -0,0 +1,8 @@
+//! Checks the diagnostics reported by the compiler on the usage of this crate.
+extern crate trybuild;
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
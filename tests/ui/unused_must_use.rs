@@ -0,0 +1,14 @@
+#![deny(unused_must_use)]
+#[macro_use]
+extern crate trackable;
+
+use trackable::error::{ErrorKindExt, Failed, TrackableError};
+
+fn main() {
+    Failed.error();
+    Failed.cause("something wrong");
+    TrackableError::new(Failed, "something wrong");
+
+    let e = Failed.error();
+    track!(e);
+}